@@ -87,3 +87,64 @@ pub fn bits_to_bytes(bits: &[bool]) -> Vec<u8> {
     }
     (bytes)
 }
+
+/// Spreads the bits of `value` out so that they occupy the even bit positions of the result
+fn spread_bits(value: u32) -> u64 {
+    let mut x = u64::from(value);
+    x = (x | (x << 16)) & 0x0000_ffff_0000_ffff;
+    x = (x | (x << 8)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555
+}
+
+/// The inverse of `spread_bits`: gathers the even bit positions of `value` together, discarding the odd ones
+fn compact_bits(value: u64) -> u32 {
+    let mut x = value & 0x5555_5555_5555_5555;
+    x = (x | (x >> 1)) & 0x3333_3333_3333_3333;
+    x = (x | (x >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x >> 4)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x >> 8)) & 0x0000_ffff_0000_ffff;
+    ((x | (x >> 16)) & 0x0000_0000_ffff_ffff) as u32
+}
+
+/// Interleaves the bits of `x` and `y` into a Morton (Z-order) code. The bits of `x` occupy the even positions and the
+/// bits of `y` the odd positions, so points that are close together in 2D tend to have keys that sort close together.
+pub fn interleave(x: u32, y: u32) -> u64 {
+    spread_bits(x) | (spread_bits(y) << 1)
+}
+
+/// Splits a Morton (Z-order) code produced by [interleave] back into its `(x, y)` coordinates
+pub fn deinterleave(code: u64) -> (u32, u32) {
+    (compact_bits(code), compact_bits(code >> 1))
+}
+
+/// Interleaves two big-endian byte strings of equal length into a Morton code of twice the length, for keys wider
+/// than 32 bits. For `u32` inputs, this gives the big-endian bytes of [interleave]. Returns None if the lengths differ.
+pub fn interleave_bytes(x: &[u8], y: &[u8]) -> Option<Vec<u8>> {
+    if x.len() != y.len() {
+        return None;
+    }
+    let mut code = Vec::with_capacity(x.len() * 2);
+    for (&a, &b) in x.iter().zip(y) {
+        let pair = interleave(u32::from(a), u32::from(b)) as u16;
+        code.extend_from_slice(&pair.to_be_bytes());
+    }
+    Some(code)
+}
+
+/// Splits a Morton code produced by [interleave_bytes] back into its `(x, y)` byte strings. Returns None if the code
+/// has an odd length.
+pub fn deinterleave_bytes(code: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    if code.len() % 2 != 0 {
+        return None;
+    }
+    let (x, y) = code
+        .chunks(2)
+        .map(|pair| {
+            let (a, b) = deinterleave(u64::from(u16::from_be_bytes([pair[0], pair[1]])));
+            (a as u8, b as u8)
+        })
+        .unzip();
+    Some((x, y))
+}
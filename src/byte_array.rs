@@ -20,15 +20,20 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::hex::{from_hex, to_hex, Hex, HexError};
+use crate::hex::{from_hex, to_hex, to_hex_upper, Hex, HexError};
+use std::convert::TryFrom;
 use thiserror::Error;
 
 #[derive(Debug, Error, PartialEq)]
 pub enum ByteArrayError {
     #[error("Could not create a ByteArray when converting from a different format: {0}")]
     ConversionError(String),
-    #[error("The input data was the incorrect length to perform the desired conversion")]
-    IncorrectLength,
+    /// For types with a bounded capacity rather than a fixed length, `expected` is the capacity
+    #[error(
+        "The input data was the incorrect length to perform the desired conversion: expected {expected} bytes, got \
+         {actual}"
+    )]
+    IncorrectLength { expected: usize, actual: usize },
 }
 
 /// Many of the types in this crate are just large numbers (256 bit usually). This trait provides the common
@@ -41,18 +46,74 @@ pub trait ByteArray: Sized {
         self.as_bytes().to_vec()
     }
 
+    /// Convert the type into a byte vector. Implementations that own a `Vec<u8>` return it without copying.
+    fn into_vec(self) -> Vec<u8> {
+        self.to_vec()
+    }
+
     /// Try and convert the given byte vector to the implemented type. Any failures (incorrect string length etc)
     /// return a [KeyError](enum.KeyError.html) with an explanatory note.
+    #[deprecated(note = "use `try_from_bytes`, which accepts any `AsRef<[u8]>`")]
     fn from_vec(v: &Vec<u8>) -> Result<Self, ByteArrayError> {
         Self::from_bytes(v.as_slice())
     }
 
+    /// Try and convert anything that can be borrowed as bytes (a `Vec<u8>`, a slice, an array, another byte array
+    /// type, etc) to the implemented type. Failures are as for [from_bytes](ByteArray::from_bytes).
+    fn try_from_bytes<B: AsRef<[u8]>>(bytes: B) -> Result<Self, ByteArrayError> {
+        Self::from_bytes(bytes.as_ref())
+    }
+
     /// Try and convert the given byte array to the implemented type. Any failures (incorrect array length,
     /// implementation-specific checks, etc) return a [ByteArrayError](enum.ByteArrayError.html).
     fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError>;
 
     /// Return the type as a byte array
     fn as_bytes(&self) -> &[u8];
+
+    /// The `to_hex` of the type's [Hex] impl, which hex-encodes [as_bytes](ByteArray::as_bytes) by default. Types
+    /// with a different hex representation (a prefix, a checksum, etc.) override this along with
+    /// [to_hex_upper_repr](ByteArray::to_hex_upper_repr) and [from_hex_repr](ByteArray::from_hex_repr).
+    fn to_hex_repr(&self) -> String {
+        to_hex(self.as_bytes())
+    }
+
+    /// The `to_hex_upper` of the type's [Hex] impl, which hex-encodes [as_bytes](ByteArray::as_bytes) with uppercase
+    /// digits by default
+    fn to_hex_upper_repr(&self) -> String {
+        to_hex_upper(self.as_bytes())
+    }
+
+    /// The `from_hex` of the type's [Hex] impl, which decodes the hex and passes the bytes to
+    /// [from_bytes](ByteArray::from_bytes) by default
+    fn from_hex_repr(hex: &str) -> Result<Self, HexError> {
+        let v = from_hex(hex)?;
+        Self::try_from_bytes(v).map_err(|_| HexError::HexConversionError)
+    }
+}
+
+/// Borrow a byte slice as a reference to the type, without copying. This is implemented by types that are a
+/// transparent wrapper around a byte array, so that parsers can view wire bytes as typed values.
+pub trait ByteArrayRef {
+    /// Fails with [ByteArrayError::IncorrectLength] if `bytes` is not exactly the length of the type
+    fn from_bytes_ref(bytes: &[u8]) -> Result<&Self, ByteArrayError>;
+}
+
+/// A [ByteArray] that is always exactly [LEN](FixedByteArray::LEN) bytes long, so that generic code can check lengths
+/// up front and convert to and from the underlying array without going through a slice.
+pub trait FixedByteArray: ByteArray {
+    /// The length of the byte representation
+    const LEN: usize;
+    /// The underlying array type, `[u8; LEN]`. Arrays longer than 32 bytes don't implement `AsRef`, `Default` or
+    /// `PartialEq` on our toolchain, so only the bounds that hold for every length are required; use
+    /// [as_bytes](ByteArray::as_bytes) for a slice view.
+    type Array: Copy + Send + Sync + 'static;
+
+    /// Wrap the given byte array
+    fn from_array(array: Self::Array) -> Self;
+
+    /// Return a reference to the underlying byte array
+    fn as_array(&self) -> &Self::Array;
 }
 
 impl ByteArray for Vec<u8> {
@@ -60,8 +121,8 @@ impl ByteArray for Vec<u8> {
         self.clone()
     }
 
-    fn from_vec(v: &Vec<u8>) -> Result<Self, ByteArrayError> {
-        Ok(v.clone())
+    fn into_vec(self) -> Vec<u8> {
+        self
     }
 
     fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
@@ -73,10 +134,34 @@ impl ByteArray for Vec<u8> {
     }
 }
 
+/// Text as its UTF-8 bytes, so that textual identifiers can use the same hex and serde helpers as binary data
+impl ByteArray for String {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        str_from_bytes(bytes).map(String::from)
+    }
+
+    fn into_vec(self) -> Vec<u8> {
+        self.into_bytes()
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        str::as_bytes(self)
+    }
+}
+
+/// Borrow `bytes` as a string slice, failing with [ByteArrayError::ConversionError] if they are not valid UTF-8. This
+/// is the borrowed counterpart to the [ByteArray] impl for `String`.
+pub fn str_from_bytes(bytes: &[u8]) -> Result<&str, ByteArrayError> {
+    std::str::from_utf8(bytes).map_err(|e| ByteArrayError::ConversionError(e.to_string()))
+}
+
 impl ByteArray for [u8; 32] {
     fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
         if bytes.len() != 32 {
-            return Err(ByteArrayError::IncorrectLength);
+            return Err(ByteArrayError::IncorrectLength {
+                expected: 32,
+                actual: bytes.len(),
+            });
         }
         let mut a = [0u8; 32];
         a.copy_from_slice(bytes);
@@ -88,13 +173,382 @@ impl ByteArray for [u8; 32] {
     }
 }
 
+impl ByteArrayRef for [u8; 32] {
+    fn from_bytes_ref(bytes: &[u8]) -> Result<&Self, ByteArrayError> {
+        <&[u8; 32]>::try_from(bytes).map_err(|_| ByteArrayError::IncorrectLength {
+            expected: 32,
+            actual: bytes.len(),
+        })
+    }
+}
+
+impl FixedByteArray for [u8; 32] {
+    type Array = [u8; 32];
+
+    const LEN: usize = 32;
+
+    fn from_array(array: [u8; 32]) -> Self {
+        array
+    }
+
+    fn as_array(&self) -> &[u8; 32] {
+        self
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<A: arrayvec::Array<Item = u8>> ByteArray for arrayvec::ArrayVec<A> {
+    /// Fails with [ByteArrayError::IncorrectLength] if `bytes` does not fit in the backing array
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        let mut v = Self::new();
+        v.try_extend_from_slice(bytes)
+            .map_err(|_| ByteArrayError::IncorrectLength {
+                expected: A::CAPACITY,
+                actual: bytes.len(),
+            })?;
+        Ok(v)
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+#[cfg(feature = "tinyvec")]
+impl<A: tinyvec::Array<Item = u8> + Default> ByteArray for tinyvec::TinyVec<A> {
+    /// Spills onto the heap if `bytes` does not fit in the backing array, so this never fails
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        Ok(Self::from(bytes))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array<Item = u8>> ByteArray for smallvec::SmallVec<A> {
+    /// Spills onto the heap if `bytes` does not fit in the backing array, so this never fails
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        Ok(Self::from_slice(bytes))
+    }
+
+    fn into_vec(self) -> Vec<u8> {
+        smallvec::SmallVec::into_vec(self)
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl ByteArray for bytes::Bytes {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        Ok(Self::copy_from_slice(bytes))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl ByteArray for bytes::BytesMut {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        Ok(Self::from(bytes))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<N: heapless::ArrayLength<u8>> ByteArray for heapless::Vec<u8, N> {
+    /// Fails with [ByteArrayError::IncorrectLength] if `bytes` does not fit in the vector's capacity
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        let mut v = Self::new();
+        v.extend_from_slice(bytes)
+            .map_err(|_| ByteArrayError::IncorrectLength {
+                expected: v.capacity(),
+                actual: bytes.len(),
+            })?;
+        Ok(v)
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ByteArray for zeroize::Zeroizing<Vec<u8>> {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        Ok(Self::new(bytes.to_vec()))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+
+    /// Decodes straight into the wiped buffer, rather than through the unprotected intermediate vector of the default.
+    /// The buffer is wrapped before decoding starts, so the bytes decoded before an invalid digit are wiped too. The
+    /// encoded string returned by `to_hex` is not wiped.
+    fn from_hex_repr(hex: &str) -> Result<Self, HexError> {
+        let (digits, _) = crate::hex::trim_hex(hex)?;
+        let mut bytes = Self::new(vec![0u8; digits.len() / 2]);
+        crate::hex::from_hex_into(hex, &mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+/// XOR two equal-length byte slices, failing with [ByteArrayError::IncorrectLength] (where `expected` is the length of
+/// `a`) instead of truncating to the shorter one
+pub fn xor(a: &[u8], b: &[u8]) -> Result<Vec<u8>, ByteArrayError> {
+    let mut out = a.to_vec();
+    xor_assign(&mut out, b)?;
+    Ok(out)
+}
+
+/// XOR `b` into `a` in place. The slices must be the same length, as for [xor].
+pub fn xor_assign(a: &mut [u8], b: &[u8]) -> Result<(), ByteArrayError> {
+    zip_assign(a, b, |x, y| x ^ y)
+}
+
+/// AND two equal-length byte slices. The slices must be the same length, as for [xor].
+pub fn and(a: &[u8], b: &[u8]) -> Result<Vec<u8>, ByteArrayError> {
+    let mut out = a.to_vec();
+    and_assign(&mut out, b)?;
+    Ok(out)
+}
+
+/// AND `b` into `a` in place. The slices must be the same length, as for [xor].
+pub fn and_assign(a: &mut [u8], b: &[u8]) -> Result<(), ByteArrayError> {
+    zip_assign(a, b, |x, y| x & y)
+}
+
+fn zip_assign<F: Fn(u8, u8) -> u8>(a: &mut [u8], b: &[u8], op: F) -> Result<(), ByteArrayError> {
+    if a.len() != b.len() {
+        return Err(ByteArrayError::IncorrectLength {
+            expected: a.len(),
+            actual: b.len(),
+        });
+    }
+    a.iter_mut().zip(b).for_each(|(x, y)| *x = op(*x, *y));
+    Ok(())
+}
+
 impl<T: ByteArray> Hex for T {
     fn from_hex(hex: &str) -> Result<Self, HexError> {
-        let v = from_hex(hex)?;
-        Self::from_vec(&v).map_err(|_| HexError::HexConversionError)
+        Self::from_hex_repr(hex)
     }
 
     fn to_hex(&self) -> String {
-        to_hex(&self.to_vec())
+        self.to_hex_repr()
+    }
+
+    fn to_hex_upper(&self) -> String {
+        self.to_hex_upper_repr()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A byte array that hex-encodes with a `0x` prefix instead of the default representation
+    #[derive(Debug, PartialEq)]
+    struct Prefixed(Vec<u8>);
+
+    impl ByteArray for Prefixed {
+        fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+            Ok(Prefixed(bytes.to_vec()))
+        }
+
+        fn as_bytes(&self) -> &[u8] {
+            &self.0
+        }
+
+        fn to_hex_repr(&self) -> String {
+            crate::hex::to_hex_prefixed(&self.0)
+        }
+
+        fn to_hex_upper_repr(&self) -> String {
+            format!("0x{}", to_hex_upper(&self.0))
+        }
+
+        fn from_hex_repr(hex: &str) -> Result<Self, HexError> {
+            Ok(Prefixed(from_hex(hex)?))
+        }
+    }
+
+    #[test]
+    fn custom_hex() {
+        let v = Prefixed::from_bytes(&[0xab, 0xcd]).unwrap();
+        assert_eq!(v.to_hex(), "0xabcd");
+        assert_eq!(v.to_hex_upper(), "0xABCD");
+        assert_eq!(Prefixed::from_hex(&v.to_hex()).unwrap(), v);
+        assert_eq!(vec![0xabu8, 0xcd].to_hex(), "abcd");
+    }
+
+    fn zeroed<T: FixedByteArray>() -> T {
+        T::from_bytes(&vec![0; T::LEN]).unwrap()
+    }
+
+    #[test]
+    fn strings() {
+        let s = String::from_hex("7461726921").unwrap();
+        assert_eq!(s, "tari!");
+        assert_eq!(s.to_hex(), "7461726921");
+        assert_eq!(str_from_bytes(b"tari").unwrap(), "tari");
+        assert!(match String::from_bytes(&[0x74, 0xff]) {
+            Err(ByteArrayError::ConversionError(msg)) => msg.contains("invalid utf-8"),
+            _ => false,
+        });
+        assert!(str_from_bytes(&[0xc3]).is_err());
+        assert_eq!("tari".to_string().into_vec(), b"tari".to_vec());
+    }
+
+    #[test]
+    fn bitwise() {
+        let pad = [0xff, 0x0f, 0x00];
+        let masked = xor(&[0x12, 0x34, 0x56], &pad).unwrap();
+        assert_eq!(masked, vec![0xed, 0x3b, 0x56]);
+        let mut unmasked = masked;
+        xor_assign(&mut unmasked, &pad).unwrap();
+        assert_eq!(unmasked, vec![0x12, 0x34, 0x56]);
+        assert_eq!(and(&[0x12, 0x34, 0x56], &pad).unwrap(), vec![0x12, 0x04, 0x00]);
+
+        assert_eq!(
+            xor(&[1, 2, 3], &[1, 2]),
+            Err(ByteArrayError::IncorrectLength { expected: 3, actual: 2 })
+        );
+        let mut a = [1u8, 2];
+        assert!(and_assign(&mut a, &[1, 2, 3]).is_err());
+        assert_eq!(a, [1, 2]);
+    }
+
+    #[test]
+    fn try_from_bytes() {
+        let owned = vec![5u8; 32];
+        assert_eq!(<[u8; 32]>::try_from_bytes(&owned).unwrap(), [5; 32]);
+        assert_eq!(<[u8; 32]>::try_from_bytes([5u8; 32]).unwrap(), [5; 32]);
+        assert_eq!(Vec::<u8>::try_from_bytes(&owned[..3]).unwrap(), vec![5; 3]);
+        assert!(<[u8; 32]>::try_from_bytes(owned).is_ok());
+        assert!(<[u8; 32]>::try_from_bytes(b"short").is_err());
+    }
+
+    #[test]
+    fn into_vec() {
+        let v = vec![1u8, 2, 3];
+        let ptr = v.as_ptr();
+        let v = ByteArray::into_vec(v);
+        assert_eq!(v.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn fixed_length() {
+        let a: [u8; 32] = zeroed();
+        assert_eq!(a, [0; 32]);
+        assert_eq!(<[u8; 32]>::from_array([7; 32]).as_array(), &[7; 32]);
+        assert_eq!([7; 32].into_vec(), vec![7; 32]);
+        let bytes = [9u8; 33];
+        let a = <[u8; 32]>::from_bytes_ref(&bytes[1..]).unwrap();
+        assert_eq!(a.as_ptr(), bytes[1..].as_ptr());
+        assert!(<[u8; 32]>::from_bytes_ref(&bytes).is_err());
+        let err = <[u8; 32]>::from_bytes(&[0; 31]).unwrap_err();
+        assert_eq!(err, ByteArrayError::IncorrectLength {
+            expected: 32,
+            actual: 31
+        });
+        assert_eq!(
+            err.to_string(),
+            "The input data was the incorrect length to perform the desired conversion: expected 32 bytes, got 31"
+        );
+    }
+
+    #[cfg(feature = "arrayvec")]
+    #[test]
+    fn arrayvec() {
+        let v = arrayvec::ArrayVec::<[u8; 4]>::from_bytes(&[1, 2, 3]).unwrap();
+        assert_eq!(v.as_bytes(), &[1, 2, 3]);
+        assert_eq!(v.to_hex(), "010203");
+        assert_eq!(
+            arrayvec::ArrayVec::<[u8; 4]>::from_bytes(&[0; 5]),
+            Err(ByteArrayError::IncorrectLength { expected: 4, actual: 5 })
+        );
+    }
+
+    #[cfg(feature = "tinyvec")]
+    #[test]
+    fn tinyvec() {
+        let v = tinyvec::TinyVec::<[u8; 4]>::from_hex("010203").unwrap();
+        assert!(matches!(v, tinyvec::TinyVec::Inline(_)));
+        assert_eq!(v.as_bytes(), &[1, 2, 3]);
+        let v = tinyvec::TinyVec::<[u8; 4]>::from_bytes(&[7; 5]).unwrap();
+        assert!(matches!(v, tinyvec::TinyVec::Heap(_)));
+        assert_eq!(v.to_vec(), vec![7; 5]);
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn smallvec() {
+        let v = smallvec::SmallVec::<[u8; 4]>::from_hex("0a0b").unwrap();
+        assert!(!v.spilled());
+        assert_eq!(v.to_hex(), "0a0b");
+        let v = smallvec::SmallVec::<[u8; 4]>::from_bytes(&[1; 8]).unwrap();
+        assert!(v.spilled());
+        assert_eq!(ByteArray::into_vec(v), vec![1; 8]);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn bytes() {
+        let b = bytes::Bytes::from_hex("cafe").unwrap();
+        assert_eq!(b, bytes::Bytes::from_static(&[0xca, 0xfe]));
+        assert_eq!(b.to_hex(), "cafe");
+        assert_eq!(b.to_vec(), vec![0xca, 0xfe]);
+
+        let mut b = bytes::BytesMut::from_bytes(&[1, 2]).unwrap();
+        b.extend_from_slice(&[3]);
+        assert_eq!(b.to_hex(), "010203");
+        assert_eq!(b.freeze().as_bytes(), &[1, 2, 3]);
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn heapless() {
+        use heapless::consts::U4;
+        let v = heapless::Vec::<u8, U4>::from_hex("a1b2c3").unwrap();
+        assert_eq!(v.as_bytes(), &[0xa1, 0xb2, 0xc3]);
+        assert_eq!(v.to_hex(), "a1b2c3");
+        assert_eq!(
+            heapless::Vec::<u8, U4>::from_bytes(&[0; 5]),
+            Err(ByteArrayError::IncorrectLength { expected: 4, actual: 5 })
+        );
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroizing() {
+        let secret = zeroize::Zeroizing::<Vec<u8>>::from_hex("5ec2e7").unwrap();
+        assert_eq!(secret.as_bytes(), &[0x5e, 0xc2, 0xe7]);
+        assert_eq!(secret.to_hex(), "5ec2e7");
+        let copy = zeroize::Zeroizing::<Vec<u8>>::from_bytes(&secret).unwrap();
+        assert_eq!(copy.to_vec(), secret.to_vec());
+        assert_eq!(zeroize::Zeroizing::<Vec<u8>>::from_hex(" 0x5ec2e7 ").unwrap(), secret);
+        assert!(zeroize::Zeroizing::<Vec<u8>>::from_hex("0x").unwrap().is_empty());
+        assert!(match zeroize::Zeroizing::<Vec<u8>>::from_hex("5ec2g7") {
+            Err(HexError::InvalidCharacter {
+                index: 4,
+                character: 'g',
+            }) => true,
+            _ => false,
+        });
+        assert!(match zeroize::Zeroizing::<Vec<u8>>::from_hex("5ec2e") {
+            Err(HexError::LengthError { length: 5 }) => true,
+            _ => false,
+        });
     }
 }
@@ -0,0 +1,251 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Cursors for parsing and writing fixed-layout binary data, such as the output of
+//! [ExtendBytes](crate::ExtendBytes). Reads and writes past the end of the data return an error instead of panicking.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ByteCursorError {
+    #[error("Unexpected end of input: {required} bytes are needed but only {remaining} remain")]
+    UnexpectedEnd { required: usize, remaining: usize },
+    #[error("The buffer is full: {required} bytes are needed but only {remaining} remain")]
+    BufferFull { required: usize, remaining: usize },
+}
+
+/// Reads values from the front of a byte slice
+///
+/// ```
+/// use tari_utilities::byte_cursor::ByteReader;
+///
+/// let mut reader = ByteReader::new(&[1, 0x34, 0x12, 0, 0, 9, 9]);
+/// assert_eq!(reader.take_u8().unwrap(), 1);
+/// assert_eq!(reader.take_u32_le().unwrap(), 0x1234);
+/// let tail: [u8; 2] = reader.take_array().unwrap();
+/// assert_eq!(tail, [9, 9]);
+/// assert!(reader.take_u8().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+macro_rules! take_int {
+    ($t:ty, $le:ident, $be:ident) => {
+        /// Read a little-endian integer
+        pub fn $le(&mut self) -> Result<$t, ByteCursorError> {
+            Ok(<$t>::from_le_bytes(self.take_array()?))
+        }
+
+        /// Read a big-endian integer
+        pub fn $be(&mut self) -> Result<$t, ByteCursorError> {
+            Ok(<$t>::from_be_bytes(self.take_array()?))
+        }
+    };
+}
+
+impl<'a> ByteReader<'a> {
+    take_int!(u16, take_u16_le, take_u16_be);
+
+    take_int!(u32, take_u32_le, take_u32_be);
+
+    take_int!(u64, take_u64_le, take_u64_be);
+
+    pub fn new(bytes: &'a [u8]) -> Self {
+        ByteReader { bytes, position: 0 }
+    }
+
+    /// The number of bytes that have been read so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The number of bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    /// The bytes that have not been read yet
+    pub fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+
+    /// Read the next `len` bytes, borrowing them from the underlying slice
+    pub fn take_bytes(&mut self, len: usize) -> Result<&'a [u8], ByteCursorError> {
+        if len > self.remaining() {
+            return Err(ByteCursorError::UnexpectedEnd {
+                required: len,
+                remaining: self.remaining(),
+            });
+        }
+        let bytes = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Read as many bytes as fit in the array type `A`, e.g. `[u8; 32]`
+    pub fn take_array<A: Default + AsMut<[u8]>>(&mut self) -> Result<A, ByteCursorError> {
+        let mut array = A::default();
+        let buf = array.as_mut();
+        buf.copy_from_slice(self.take_bytes(buf.len())?);
+        Ok(array)
+    }
+
+    pub fn take_u8(&mut self) -> Result<u8, ByteCursorError> {
+        Ok(self.take_bytes(1)?[0])
+    }
+}
+
+/// Writes values into a fixed-size buffer, front to back
+///
+/// ```
+/// use tari_utilities::byte_cursor::ByteWriter;
+///
+/// let mut buf = [0u8; 5];
+/// let mut writer = ByteWriter::new(&mut buf);
+/// writer.put_u8(1).unwrap();
+/// writer.put_u32_le(0x1234).unwrap();
+/// assert!(writer.put_u8(2).is_err());
+/// assert_eq!(buf, [1, 0x34, 0x12, 0, 0]);
+/// ```
+#[derive(Debug)]
+pub struct ByteWriter<'a> {
+    buf: &'a mut [u8],
+    position: usize,
+}
+
+macro_rules! put_int {
+    ($t:ty, $le:ident, $be:ident) => {
+        /// Write a little-endian integer
+        pub fn $le(&mut self, value: $t) -> Result<(), ByteCursorError> {
+            self.put_bytes(&value.to_le_bytes())
+        }
+
+        /// Write a big-endian integer
+        pub fn $be(&mut self, value: $t) -> Result<(), ByteCursorError> {
+            self.put_bytes(&value.to_be_bytes())
+        }
+    };
+}
+
+impl<'a> ByteWriter<'a> {
+    put_int!(u16, put_u16_le, put_u16_be);
+
+    put_int!(u32, put_u32_le, put_u32_be);
+
+    put_int!(u64, put_u64_le, put_u64_be);
+
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        ByteWriter { buf, position: 0 }
+    }
+
+    /// The number of bytes that have been written so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The number of bytes that can still be written
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.position
+    }
+
+    /// The part of the buffer that has been written
+    pub fn written(&self) -> &[u8] {
+        &self.buf[..self.position]
+    }
+
+    /// Write all of `bytes`, or nothing if they don't fit
+    pub fn put_bytes(&mut self, bytes: &[u8]) -> Result<(), ByteCursorError> {
+        if bytes.len() > self.remaining() {
+            return Err(ByteCursorError::BufferFull {
+                required: bytes.len(),
+                remaining: self.remaining(),
+            });
+        }
+        self.buf[self.position..self.position + bytes.len()].copy_from_slice(bytes);
+        self.position += bytes.len();
+        Ok(())
+    }
+
+    pub fn put_u8(&mut self, value: u8) -> Result<(), ByteCursorError> {
+        self.put_bytes(&[value])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ExtendBytes;
+
+    #[test]
+    fn read_extend_bytes_output() {
+        let mut buf = Vec::new();
+        7u16.append_raw_bytes(&mut buf);
+        0xdead_beefu32.append_raw_bytes(&mut buf);
+        u64::max_value().append_raw_bytes(&mut buf);
+        "hi".append_raw_bytes(&mut buf);
+
+        let mut reader = ByteReader::new(&buf);
+        assert_eq!(reader.take_u16_le().unwrap(), 7);
+        assert_eq!(reader.take_u32_le().unwrap(), 0xdead_beef);
+        assert_eq!(reader.take_u64_le().unwrap(), u64::max_value());
+        assert_eq!(reader.position(), 14);
+        assert_eq!(reader.rest(), b"hi");
+        assert_eq!(
+            reader.take_u32_be(),
+            Err(ByteCursorError::UnexpectedEnd {
+                required: 4,
+                remaining: 2
+            })
+        );
+        // A failed read doesn't consume anything
+        assert_eq!(reader.take_bytes(2).unwrap(), b"hi");
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut buf = [0u8; 15];
+        let mut writer = ByteWriter::new(&mut buf);
+        writer.put_u16_be(0x0102).unwrap();
+        writer.put_u64_be(3).unwrap();
+        writer.put_bytes(&[4, 5, 6, 7]).unwrap();
+        assert_eq!(
+            writer.put_u16_le(1),
+            Err(ByteCursorError::BufferFull {
+                required: 2,
+                remaining: 1
+            })
+        );
+        writer.put_u8(8).unwrap();
+        assert_eq!(writer.remaining(), 0);
+        assert_eq!(writer.written().len(), 15);
+
+        let mut reader = ByteReader::new(&buf);
+        assert_eq!(reader.take_u16_be().unwrap(), 0x0102);
+        assert_eq!(reader.take_u64_be().unwrap(), 3);
+        assert_eq!(reader.take_array::<[u8; 4]>().unwrap(), [4, 5, 6, 7]);
+        assert_eq!(reader.take_u8().unwrap(), 8);
+    }
+}
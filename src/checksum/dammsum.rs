@@ -0,0 +1,186 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! DammSum, a check symbol for strings of k-bit symbols (e.g. bytes, or base32 digits) built on a totally
+//! anti-symmetric quasigroup, as used by Tari addresses. A single check symbol detects every single-symbol
+//! substitution and every transposition of adjacent symbols.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DammSumError {
+    #[error("DammSum supports 2 to 8 bits per symbol, not {0}")]
+    UnsupportedSymbolSize(u8),
+    #[error("Symbol {symbol} at index {index} does not fit in {bits} bits")]
+    InvalidSymbol { index: usize, symbol: u8, bits: u8 },
+    #[error("There is no check symbol to verify")]
+    MissingChecksum,
+    #[error("The check symbol does not match the data")]
+    ChecksumMismatch,
+}
+
+/// The low coefficients of an irreducible polynomial of each degree from 2 to 8, used to reduce products in GF(2^k)
+const REDUCTION: [u16; 7] = [
+    0b11,        // x^2 + x + 1
+    0b011,       // x^3 + x + 1
+    0b0011,      // x^4 + x + 1
+    0b0_0101,    // x^5 + x^2 + 1
+    0b00_0011,   // x^6 + x + 1
+    0b000_0011,  // x^7 + x + 1
+    0b0001_1011, // x^8 + x^4 + x^3 + x + 1
+];
+
+/// DammSum over symbols of a fixed number of bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DammSum {
+    bits: u8,
+}
+
+impl DammSum {
+    /// DammSum over bytes, as used by Tari addresses
+    pub const BYTES: DammSum = DammSum { bits: 8 };
+
+    /// DammSum over symbols of `bits` bits, which must be between 2 and 8
+    pub fn new(bits: u8) -> Result<Self, DammSumError> {
+        if bits < 2 || bits > 8 {
+            return Err(DammSumError::UnsupportedSymbolSize(bits));
+        }
+        Ok(DammSum { bits })
+    }
+
+    /// The number of bits in each symbol
+    pub fn bits(self) -> u8 {
+        self.bits
+    }
+
+    /// Calculate the check symbol for `symbols`
+    pub fn checksum(self, symbols: &[u8]) -> Result<u8, DammSumError> {
+        let top = 1u16 << (self.bits - 1);
+        let full = (1u16 << self.bits) - 1;
+        let reduction = REDUCTION[self.bits as usize - 2];
+        let mut result = 0u16;
+        for (index, &symbol) in symbols.iter().enumerate() {
+            if u16::from(symbol) > full {
+                return Err(DammSumError::InvalidSymbol {
+                    index,
+                    symbol,
+                    bits: self.bits,
+                });
+            }
+            // The quasigroup operation is a * x + b in GF(2^k)
+            result ^= u16::from(symbol);
+            let overflow = result & top != 0;
+            result = (result << 1) & full;
+            if overflow {
+                result ^= reduction;
+            }
+        }
+        Ok(result as u8)
+    }
+
+    /// Return `symbols` with the check symbol appended
+    pub fn encode(self, symbols: &[u8]) -> Result<Vec<u8>, DammSumError> {
+        let check = self.checksum(symbols)?;
+        let mut encoded = Vec::with_capacity(symbols.len() + 1);
+        encoded.extend_from_slice(symbols);
+        encoded.push(check);
+        Ok(encoded)
+    }
+
+    /// Check that the last symbol of `symbols` is the check symbol for the rest, and return the rest
+    pub fn verify(self, symbols: &[u8]) -> Result<&[u8], DammSumError> {
+        if symbols.is_empty() {
+            return Err(DammSumError::MissingChecksum);
+        }
+        // Appending the check symbol always brings the checksum to zero
+        if self.checksum(symbols)? != 0 {
+            return Err(DammSumError::ChecksumMismatch);
+        }
+        Ok(&symbols[..symbols.len() - 1])
+    }
+}
+
+/// Calculate the DammSum check byte for `bytes`
+pub fn dammsum(bytes: &[u8]) -> u8 {
+    DammSum::BYTES
+        .checksum(bytes)
+        .expect("every byte is a valid 8-bit symbol")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for bits in 2..=8 {
+            let damm = DammSum::new(bits).unwrap();
+            let data = (0..50u8)
+                .map(|i| i.wrapping_mul(37) % (1 << (bits - 1)))
+                .collect::<Vec<_>>();
+            let encoded = damm.encode(&data).unwrap();
+            assert_eq!(damm.verify(&encoded).unwrap(), &data[..]);
+        }
+        assert_eq!(dammsum(b""), 0);
+        assert_eq!(DammSum::BYTES.verify(&[0]), Ok(&[][..]));
+    }
+
+    #[test]
+    fn detects_substitutions_and_transpositions() {
+        for bits in 2..=8 {
+            let damm = DammSum::new(bits).unwrap();
+            let symbols = 1u16 << bits;
+            let data = (0..12u16).map(|i| (i * 101 % symbols) as u8).collect::<Vec<_>>();
+            let encoded = damm.encode(&data).unwrap();
+            for i in 0..encoded.len() {
+                for s in (0..symbols).map(|s| s as u8) {
+                    if s != encoded[i] {
+                        let mut changed = encoded.clone();
+                        changed[i] = s;
+                        assert_eq!(damm.verify(&changed), Err(DammSumError::ChecksumMismatch));
+                    }
+                }
+                if i + 1 < encoded.len() && encoded[i] != encoded[i + 1] {
+                    let mut swapped = encoded.clone();
+                    swapped.swap(i, i + 1);
+                    assert_eq!(damm.verify(&swapped), Err(DammSumError::ChecksumMismatch));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(DammSum::new(1), Err(DammSumError::UnsupportedSymbolSize(1)));
+        assert_eq!(DammSum::new(9), Err(DammSumError::UnsupportedSymbolSize(9)));
+        let damm = DammSum::new(5).unwrap();
+        assert_eq!(
+            damm.checksum(&[1, 31, 32]),
+            Err(DammSumError::InvalidSymbol {
+                index: 2,
+                symbol: 32,
+                bits: 5
+            })
+        );
+        assert_eq!(damm.verify(&[]), Err(DammSumError::MissingChecksum));
+    }
+}
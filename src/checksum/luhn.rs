@@ -0,0 +1,192 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! The Luhn mod N algorithm, which generalises the Luhn check digit used by credit card numbers to any alphabet size.
+//! A check digit catches every single-digit typo and most transpositions of adjacent digits, so it suits short codes
+//! and base32 or base58 strings that people type in by hand.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum LuhnError {
+    #[error("Luhn mod N needs an alphabet of 2 to 256 digits, not {0}")]
+    InvalidAlphabetSize(usize),
+    #[error("Digit {digit} at index {index} is not less than the alphabet size {size}")]
+    InvalidDigit { index: usize, digit: u8, size: usize },
+    #[error("Invalid character '{character}' at index {index}")]
+    InvalidCharacter { index: usize, character: char },
+    #[error("There is no check digit to verify")]
+    MissingCheckDigit,
+    #[error("The check digit does not match the data")]
+    ChecksumMismatch,
+}
+
+/// Luhn mod N over digits with values from 0 to N - 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LuhnModN {
+    size: usize,
+}
+
+impl LuhnModN {
+    /// Luhn mod N for an alphabet of `size` digits, which must be between 2 and 256
+    pub fn new(size: usize) -> Result<Self, LuhnError> {
+        if size < 2 || size > 256 {
+            return Err(LuhnError::InvalidAlphabetSize(size));
+        }
+        Ok(LuhnModN { size })
+    }
+
+    /// The number of digits in the alphabet
+    pub fn size(self) -> usize {
+        self.size
+    }
+
+    /// Calculate the check digit for `digits`
+    pub fn check_digit(self, digits: &[u8]) -> Result<u8, LuhnError> {
+        let sum = self.sum(digits, 2)?;
+        Ok(((self.size - sum) % self.size) as u8)
+    }
+
+    /// Return `digits` with the check digit appended
+    pub fn encode(self, digits: &[u8]) -> Result<Vec<u8>, LuhnError> {
+        let check = self.check_digit(digits)?;
+        let mut encoded = Vec::with_capacity(digits.len() + 1);
+        encoded.extend_from_slice(digits);
+        encoded.push(check);
+        Ok(encoded)
+    }
+
+    /// Check that the last digit of `digits` is the check digit for the rest, and return the rest
+    pub fn verify(self, digits: &[u8]) -> Result<&[u8], LuhnError> {
+        if digits.is_empty() {
+            return Err(LuhnError::MissingCheckDigit);
+        }
+        if self.sum(digits, 1)? != 0 {
+            return Err(LuhnError::ChecksumMismatch);
+        }
+        Ok(&digits[..digits.len() - 1])
+    }
+
+    /// Sum the digits from the right, doubling every other one starting with the given factor, modulo the alphabet
+    /// size
+    fn sum(self, digits: &[u8], mut factor: usize) -> Result<usize, LuhnError> {
+        let mut sum = 0;
+        for (index, &digit) in digits.iter().enumerate().rev() {
+            if usize::from(digit) >= self.size {
+                return Err(LuhnError::InvalidDigit {
+                    index,
+                    digit,
+                    size: self.size,
+                });
+            }
+            let addend = factor * usize::from(digit);
+            // Add the "digits" of the addend written in base N
+            sum += addend / self.size + addend % self.size;
+            factor = 3 - factor;
+        }
+        Ok(sum % self.size)
+    }
+}
+
+/// Calculate the check character for `s`, whose characters must all be in `alphabet`
+pub fn luhn_check_char(s: &str, alphabet: &str) -> Result<char, LuhnError> {
+    let alphabet = alphabet.chars().collect::<Vec<_>>();
+    let digits = to_digits(s, &alphabet)?;
+    let check = LuhnModN::new(alphabet.len())?.check_digit(&digits)?;
+    Ok(alphabet[check as usize])
+}
+
+/// Check that the last character of `s` is the check character for the rest, whose characters must all be in
+/// `alphabet`, and return the rest
+pub fn luhn_verify<'a>(s: &'a str, alphabet: &str) -> Result<&'a str, LuhnError> {
+    let alphabet = alphabet.chars().collect::<Vec<_>>();
+    let digits = to_digits(s, &alphabet)?;
+    LuhnModN::new(alphabet.len())?.verify(&digits)?;
+    let (check_index, _) = s.char_indices().last().expect("a verified string is not empty");
+    Ok(&s[..check_index])
+}
+
+fn to_digits(s: &str, alphabet: &[char]) -> Result<Vec<u8>, LuhnError> {
+    s.char_indices()
+        .map(|(index, character)| {
+            alphabet
+                .iter()
+                .position(|&c| c == character)
+                .map(|digit| digit as u8)
+                .ok_or(LuhnError::InvalidCharacter { index, character })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DECIMAL: &str = "0123456789";
+
+    #[test]
+    fn decimal_luhn() {
+        assert_eq!(luhn_check_char("7992739871", DECIMAL), Ok('3'));
+        assert_eq!(luhn_verify("79927398713", DECIMAL), Ok("7992739871"));
+        assert_eq!(luhn_verify("79927398710", DECIMAL), Err(LuhnError::ChecksumMismatch));
+        assert_eq!(luhn_verify("79927398731", DECIMAL), Err(LuhnError::ChecksumMismatch));
+    }
+
+    #[test]
+    fn mod_n() {
+        assert_eq!(luhn_check_char("abcdef", "abcdef"), Ok('e'));
+        assert_eq!(luhn_verify("abcdefe", "abcdef"), Ok("abcdef"));
+        let luhn = LuhnModN::new(58).unwrap();
+        let data = (0..40u16).map(|i| (i * 7 % 58) as u8).collect::<Vec<_>>();
+        let encoded = luhn.encode(&data).unwrap();
+        assert_eq!(luhn.verify(&encoded), Ok(&data[..]));
+        for i in 0..encoded.len() {
+            for d in (0..58).filter(|&d| d != encoded[i]) {
+                let mut changed = encoded.clone();
+                changed[i] = d;
+                assert_eq!(luhn.verify(&changed), Err(LuhnError::ChecksumMismatch));
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(LuhnModN::new(1), Err(LuhnError::InvalidAlphabetSize(1)));
+        assert_eq!(LuhnModN::new(257), Err(LuhnError::InvalidAlphabetSize(257)));
+        assert_eq!(
+            LuhnModN::new(10).unwrap().check_digit(&[1, 10]),
+            Err(LuhnError::InvalidDigit {
+                index: 1,
+                digit: 10,
+                size: 10
+            })
+        );
+        assert_eq!(
+            luhn_check_char("12a4", DECIMAL),
+            Err(LuhnError::InvalidCharacter {
+                index: 2,
+                character: 'a'
+            })
+        );
+        assert_eq!(luhn_verify("", DECIMAL), Err(LuhnError::MissingCheckDigit));
+    }
+}
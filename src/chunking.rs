@@ -0,0 +1,183 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Split byte slices into fixed-size chunks, e.g. for transports with a fixed cell size, and reassemble them. What
+//! happens to a final partial chunk is chosen with a [Padding] policy.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ChunkError {
+    #[error("A chunk size of {0} is not valid for this padding policy")]
+    InvalidChunkSize(usize),
+    #[error("The data is {length} bytes long, which is not a multiple of the chunk size {chunk_size}")]
+    UnalignedLength { length: usize, chunk_size: usize },
+    #[error("Chunk {index} is {actual} bytes long, but chunks must be {expected} bytes")]
+    IncorrectChunkLength {
+        index: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("The padding at the end of the final chunk is invalid")]
+    InvalidPadding,
+}
+
+/// How to fill out the final chunk when the data is not a multiple of the chunk size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    /// Pad the final chunk with zeros. The padding can't be told apart from the data, so [unchunk] leaves it in place.
+    Zero,
+    /// Fail with [ChunkError::UnalignedLength] if the data is not a multiple of the chunk size
+    Error,
+    /// PKCS#7: always add between 1 and `chunk_size` bytes, each holding the number of padding bytes. The chunk size
+    /// must be at most 255. [unchunk] checks and removes the padding.
+    Pkcs7,
+}
+
+/// Split `bytes` into chunks of exactly `chunk_size` bytes, padding the final chunk according to `padding`
+pub fn chunk(bytes: &[u8], chunk_size: usize, padding: Padding) -> Result<Vec<Vec<u8>>, ChunkError> {
+    check_chunk_size(chunk_size, padding)?;
+    let partial = bytes.len() % chunk_size;
+    let mut chunks = bytes.chunks(chunk_size).map(<[u8]>::to_vec).collect::<Vec<_>>();
+    match padding {
+        Padding::Zero if partial > 0 => chunks.last_mut().unwrap().resize(chunk_size, 0),
+        Padding::Zero => {},
+        Padding::Error if partial > 0 => {
+            return Err(ChunkError::UnalignedLength {
+                length: bytes.len(),
+                chunk_size,
+            })
+        },
+        Padding::Error => {},
+        Padding::Pkcs7 if partial > 0 => {
+            let pad = chunk_size - partial;
+            chunks.last_mut().unwrap().resize(chunk_size, pad as u8);
+        },
+        Padding::Pkcs7 => chunks.push(vec![chunk_size as u8; chunk_size]),
+    }
+    Ok(chunks)
+}
+
+/// Reassemble chunks produced by [chunk] with the same `chunk_size` and `padding`. Every chunk must be exactly
+/// `chunk_size` bytes long.
+pub fn unchunk<C: AsRef<[u8]>>(chunks: &[C], chunk_size: usize, padding: Padding) -> Result<Vec<u8>, ChunkError> {
+    check_chunk_size(chunk_size, padding)?;
+    let mut bytes = Vec::with_capacity(chunks.len() * chunk_size);
+    for (index, chunk) in chunks.iter().enumerate() {
+        let chunk = chunk.as_ref();
+        if chunk.len() != chunk_size {
+            return Err(ChunkError::IncorrectChunkLength {
+                index,
+                expected: chunk_size,
+                actual: chunk.len(),
+            });
+        }
+        bytes.extend_from_slice(chunk);
+    }
+    if padding == Padding::Pkcs7 {
+        let pad = usize::from(*bytes.last().ok_or(ChunkError::InvalidPadding)?);
+        if pad == 0 || pad > chunk_size || bytes[bytes.len() - pad..].iter().any(|&b| usize::from(b) != pad) {
+            return Err(ChunkError::InvalidPadding);
+        }
+        bytes.truncate(bytes.len() - pad);
+    }
+    Ok(bytes)
+}
+
+fn check_chunk_size(chunk_size: usize, padding: Padding) -> Result<(), ChunkError> {
+    if chunk_size == 0 || (padding == Padding::Pkcs7 && chunk_size > 255) {
+        return Err(ChunkError::InvalidChunkSize(chunk_size));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn padding_policies() {
+        let data = [1u8, 2, 3, 4, 5];
+        assert_eq!(chunk(&data, 2, Padding::Zero).unwrap(), vec![
+            vec![1, 2],
+            vec![3, 4],
+            vec![5, 0]
+        ]);
+        assert_eq!(
+            chunk(&data, 2, Padding::Error),
+            Err(ChunkError::UnalignedLength {
+                length: 5,
+                chunk_size: 2
+            })
+        );
+        assert_eq!(chunk(&data[..4], 2, Padding::Error).unwrap(), vec![vec![1, 2], vec![
+            3, 4
+        ]]);
+        assert_eq!(chunk(&data, 4, Padding::Pkcs7).unwrap(), vec![vec![1, 2, 3, 4], vec![
+            5, 3, 3, 3
+        ]]);
+        assert_eq!(chunk(&data[..4], 4, Padding::Pkcs7).unwrap(), vec![
+            vec![1, 2, 3, 4],
+            vec![4; 4]
+        ]);
+        assert_eq!(chunk(&[], 3, Padding::Zero).unwrap(), Vec::<Vec<u8>>::new());
+        assert_eq!(chunk(&data, 0, Padding::Zero), Err(ChunkError::InvalidChunkSize(0)));
+        assert_eq!(
+            chunk(&data, 256, Padding::Pkcs7),
+            Err(ChunkError::InvalidChunkSize(256))
+        );
+    }
+
+    #[test]
+    fn reassembly() {
+        let data = (0..100u8).collect::<Vec<_>>();
+        for &size in &[1, 7, 10, 255] {
+            for &padding in &[Padding::Pkcs7, Padding::Zero] {
+                let chunks = chunk(&data, size, padding).unwrap();
+                let joined = unchunk(&chunks, size, padding).unwrap();
+                assert_eq!(&joined[..100], &data[..]);
+            }
+        }
+        let chunks = chunk(&data, 7, Padding::Pkcs7).unwrap();
+        assert_eq!(unchunk(&chunks, 7, Padding::Pkcs7).unwrap(), data);
+        assert_eq!(
+            unchunk(&chunks[..3], 6, Padding::Pkcs7),
+            Err(ChunkError::IncorrectChunkLength {
+                index: 0,
+                expected: 6,
+                actual: 7
+            })
+        );
+        assert_eq!(
+            unchunk(&[[1u8, 2, 3]], 3, Padding::Pkcs7),
+            Err(ChunkError::InvalidPadding)
+        );
+        assert_eq!(
+            unchunk(&[[1u8, 0, 0]], 3, Padding::Pkcs7),
+            Err(ChunkError::InvalidPadding)
+        );
+        assert_eq!(
+            unchunk::<[u8; 3]>(&[], 3, Padding::Pkcs7),
+            Err(ChunkError::InvalidPadding)
+        );
+    }
+}
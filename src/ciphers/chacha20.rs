@@ -187,7 +187,7 @@ where D: ByteArray
         sized_key.clear();
         sized_nonce.clear();
 
-        Ok(D::from_vec(&plain_text)?)
+        Ok(D::try_from_bytes(plain_text)?)
     }
 
     fn seal_with_integral_nonce(plain_text: &D, key: &[u8]) -> Result<Vec<u8>, CipherError> {
@@ -240,7 +240,7 @@ where D: ByteArray
         sized_key.clear();
         nonce.clear();
 
-        Ok(D::from_vec(&plain_text)?)
+        Ok(D::try_from_bytes(plain_text)?)
     }
 }
 
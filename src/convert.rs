@@ -36,6 +36,25 @@ where
     Ok(result)
 }
 
+/// Tries to convert a series of `T`s to `U`s. Unlike [try_convert_all], every item is converted; the successes are
+/// returned in order along with each failure and the index of the item that caused it.
+pub fn partition_convert<T, U, I>(into_iter: I) -> (Vec<U>, Vec<(usize, T::Error)>)
+where
+    I: IntoIterator<Item = T>,
+    T: TryInto<U>,
+{
+    let iter = into_iter.into_iter();
+    let mut converted = Vec::with_capacity(iter.size_hint().0);
+    let mut errors = Vec::new();
+    for (i, item) in iter.enumerate() {
+        match item.try_into() {
+            Ok(v) => converted.push(v),
+            Err(e) => errors.push((i, e)),
+        }
+    }
+    (converted, errors)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -51,4 +70,15 @@ mod test {
         let err = try_convert_all::<_, u32, _>(vec![std::i64::MAX, 2, 3, 4, 5]).unwrap_err();
         assert_eq!(err.to_string(), "out of range integral type conversion attempted");
     }
+
+    #[test]
+    fn partition_convert_collects_all_errors() {
+        let (ok, errors) = partition_convert::<_, u8, _>(vec![1i64, -2, 3, 256, 5]);
+        assert_eq!(ok, [1, 3, 5]);
+        assert_eq!(errors.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [1, 3]);
+
+        let (ok, errors) = partition_convert::<_, u8, _>(vec![1i64, 2]);
+        assert_eq!(ok, [1, 2]);
+        assert!(errors.is_empty());
+    }
 }
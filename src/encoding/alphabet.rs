@@ -0,0 +1,242 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Custom alphabets for the base58 and base32 encoders, e.g. the Ripple or Flickr base58 alphabets. An alphabet is
+//! validated when it is built, and can map extra characters onto its symbols (such as `O` onto `0`) so that common
+//! misreadings still decode.
+
+use std::{borrow::Cow, fmt};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum AlphabetError {
+    #[error("An alphabet can have 2 to 128 symbols, not {0}")]
+    InvalidSize(usize),
+    #[error("Alphabets can only contain ASCII characters, but found '{0}'")]
+    NonAscii(char),
+    #[error("The character '{0}' appears in the alphabet more than once")]
+    Duplicate(char),
+    #[error("'{0}' is not a symbol of the alphabet")]
+    UnknownSymbol(char),
+}
+
+/// Marks the characters in [Alphabet::values] that are not part of the alphabet
+const INVALID: u8 = 0xff;
+
+/// The symbols used to write the digits of a base-N encoding, in order of value
+#[derive(Clone)]
+pub struct Alphabet {
+    symbols: Cow<'static, str>,
+    /// The value of each ASCII character, or [INVALID]
+    values: [u8; 128],
+}
+
+impl Alphabet {
+    /// Build an alphabet from its symbols, in order of value. The symbols must be distinct ASCII characters.
+    pub fn new(symbols: &str) -> Result<Self, AlphabetError> {
+        if let Some(c) = symbols.chars().find(|c| !c.is_ascii()) {
+            return Err(AlphabetError::NonAscii(c));
+        }
+        if symbols.len() < 2 || symbols.len() > 128 {
+            return Err(AlphabetError::InvalidSize(symbols.len()));
+        }
+        let mut values = [INVALID; 128];
+        for (value, c) in symbols.bytes().enumerate() {
+            if values[usize::from(c)] != INVALID {
+                return Err(AlphabetError::Duplicate(char::from(c)));
+            }
+            values[usize::from(c)] = value as u8;
+        }
+        Ok(Alphabet {
+            symbols: Cow::Owned(symbols.to_string()),
+            values,
+        })
+    }
+
+    /// Also accept `alias` when decoding, as a stand-in for `symbol`
+    pub fn with_alias(mut self, alias: char, symbol: char) -> Result<Self, AlphabetError> {
+        let value = self.value(symbol).ok_or(AlphabetError::UnknownSymbol(symbol))?;
+        if !alias.is_ascii() {
+            return Err(AlphabetError::NonAscii(alias));
+        }
+        if self.value(alias).is_some() {
+            return Err(AlphabetError::Duplicate(alias));
+        }
+        self.values[alias as usize] = value;
+        Ok(self)
+    }
+
+    /// Also accept the other case of every letter in the alphabet when decoding. Fails if both cases of a letter are
+    /// already in use.
+    pub fn case_insensitive(self) -> Result<Self, AlphabetError> {
+        let letters = self
+            .symbols
+            .chars()
+            .filter(char::is_ascii_alphabetic)
+            .collect::<Vec<_>>();
+        letters.into_iter().try_fold(self, |alphabet, c| {
+            let other = if c.is_ascii_lowercase() {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            };
+            alphabet.with_alias(other, c)
+        })
+    }
+
+    /// The number of symbols, which is the base of the encoding
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Always false, since an alphabet has at least two symbols
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// The symbols, in order of value
+    pub fn symbols(&self) -> &str {
+        &self.symbols
+    }
+
+    /// The value of the character `c`, if it is a symbol of the alphabet or an alias of one
+    pub fn value(&self, c: char) -> Option<u8> {
+        if !c.is_ascii() {
+            return None;
+        }
+        self.value_of_byte(c as u8)
+    }
+
+    /// The symbol for `value`, which must be less than the size of the alphabet
+    pub(crate) fn symbol(&self, value: u8) -> u8 {
+        self.symbols.as_bytes()[usize::from(value)]
+    }
+
+    /// The value of the byte `c` of some UTF-8 input, if it is a symbol of the alphabet or an alias of one
+    pub(crate) fn value_of_byte(&self, c: u8) -> Option<u8> {
+        match self.values.get(usize::from(c)) {
+            Some(&INVALID) | None => None,
+            Some(&v) => Some(v),
+        }
+    }
+}
+
+impl fmt::Debug for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Alphabet").field(&self.symbols).finish()
+    }
+}
+
+/// Bitcoin's base58 alphabet, which is also Monero's and the default
+pub const BASE58_BITCOIN: Alphabet = Alphabet {
+    symbols: Cow::Borrowed("123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz"),
+    values: [
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, 0, 1, 2, 3, 4, 5, 6, 7, 8, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, 9, 10, 11,
+        12, 13, 14, 15, 16, INVALID, 17, 18, 19, 20, 21, INVALID, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, INVALID, 44, 45, 46,
+        47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, INVALID, INVALID, INVALID, INVALID, INVALID,
+    ],
+};
+
+/// The base58 alphabet of Ripple addresses
+pub const BASE58_RIPPLE: Alphabet = Alphabet {
+    symbols: Cow::Borrowed("rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz"),
+    values: [
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, 50, 33, 7, 21, 41, 40, 27, 45, 8, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, 54,
+        10, 38, 12, 14, 47, 15, 16, INVALID, 17, 18, 19, 20, 13, INVALID, 22, 23, 24, 25, 26, 11, 28, 29, 30, 31, 32,
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, 5, 34, 35, 36, 37, 6, 39, 3, 49, 42, 43, INVALID, 44, 4,
+        46, 1, 48, 0, 2, 51, 52, 53, 9, 55, 56, 57, INVALID, INVALID, INVALID, INVALID, INVALID,
+    ],
+};
+
+/// The base58 alphabet of Flickr short URLs
+pub const BASE58_FLICKR: Alphabet = Alphabet {
+    symbols: Cow::Borrowed("123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ"),
+    values: [
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID,
+        INVALID, 0, 1, 2, 3, 4, 5, 6, 7, 8, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, 34, 35, 36,
+        37, 38, 39, 40, 41, INVALID, 42, 43, 44, 45, 46, INVALID, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, INVALID,
+        INVALID, INVALID, INVALID, INVALID, INVALID, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, INVALID, 20, 21, 22,
+        23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, INVALID, INVALID, INVALID, INVALID, INVALID,
+    ],
+};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn built_in_tables() {
+        for alphabet in &[BASE58_BITCOIN, BASE58_RIPPLE, BASE58_FLICKR] {
+            let rebuilt = Alphabet::new(alphabet.symbols()).unwrap();
+            assert_eq!(alphabet.len(), 58);
+            assert_eq!(&alphabet.values[..], &rebuilt.values[..]);
+        }
+    }
+
+    #[test]
+    fn aliases() {
+        let alphabet = Alphabet::new("0123456789abcdef")
+            .unwrap()
+            .case_insensitive()
+            .unwrap()
+            .with_alias('o', '0')
+            .unwrap();
+        assert_eq!(alphabet.value('b'), Some(11));
+        assert_eq!(alphabet.value('B'), Some(11));
+        assert_eq!(alphabet.value('o'), Some(0));
+        assert_eq!(alphabet.value('g'), None);
+        assert_eq!(alphabet.value('é'), None);
+        assert_eq!(alphabet.symbol(11), b'b');
+    }
+
+    #[test]
+    fn invalid_alphabets() {
+        assert_eq!(Alphabet::new("0").unwrap_err(), AlphabetError::InvalidSize(1));
+        assert_eq!(Alphabet::new("01é").unwrap_err(), AlphabetError::NonAscii('é'));
+        assert_eq!(Alphabet::new("0120").unwrap_err(), AlphabetError::Duplicate('0'));
+        assert_eq!(
+            BASE58_BITCOIN.case_insensitive().unwrap_err(),
+            AlphabetError::Duplicate('a')
+        );
+        let alphabet = Alphabet::new("01").unwrap();
+        assert_eq!(
+            alphabet.clone().with_alias('o', '2').unwrap_err(),
+            AlphabetError::UnknownSymbol('2')
+        );
+        assert_eq!(
+            alphabet.with_alias('1', '0').unwrap_err(),
+            AlphabetError::Duplicate('1')
+        );
+    }
+}
@@ -0,0 +1,343 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Crockford's base32, which is easier than base58 for people to read aloud and type: decoding is case-insensitive,
+//! ignores hyphens and folds the easily confused `O`, `I` and `L` into `0` and `1`. An optional check symbol (the
+//! encoded value modulo 37) catches single-character typos and transpositions of adjacent characters.
+
+use crate::{encoding::alphabet::Alphabet, ByteArray};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Base32Error {
+    #[error("Only Crockford base32 characters are permitted, but found '{character}' at position {index}")]
+    InvalidCharacter { index: usize, character: char },
+    #[error("{0} base32 characters can't encode a whole number of bytes")]
+    InvalidLength(usize),
+    #[error("The unused bits of the final base32 character must be zero")]
+    NonZeroPadding,
+    #[error("The check symbol does not match the data. Check the value for typos")]
+    ChecksumMismatch,
+    #[error("An alphabet of {0} symbols can't be used for base32, which needs 32")]
+    InvalidAlphabet(usize),
+    #[error("{0}")]
+    ByteArrayError(#[from] crate::ByteArrayError),
+}
+
+/// Conversions to and from Crockford base32, provided for every [ByteArray]
+pub trait Base32 {
+    fn from_base32(s: &str) -> Result<Self, Base32Error>
+    where Self: Sized;
+
+    fn to_base32(&self) -> String;
+}
+
+impl<T: ByteArray> Base32 for T {
+    fn from_base32(s: &str) -> Result<Self, Base32Error> {
+        let bytes = from_base32(s)?;
+        Ok(Self::try_from_bytes(bytes)?)
+    }
+
+    fn to_base32(&self) -> String {
+        to_base32(self.as_bytes())
+    }
+}
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+/// The RFC 4648 alphabet, which multibase uses
+const RFC4648_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// The extra symbols for check values 32 to 36
+const CHECK_SYMBOLS: &[u8; 5] = b"*~$=U";
+
+/// Encode the provided bytes into Crockford base32, without padding
+pub fn to_base32(bytes: &[u8]) -> String {
+    encode(bytes, ALPHABET)
+}
+
+/// Encode the provided bytes into unpadded base32 with a custom alphabet, which must have 32 symbols
+pub fn to_base32_with(bytes: &[u8], alphabet: &Alphabet) -> Result<String, Base32Error> {
+    check_alphabet(alphabet)?;
+    Ok(encode(bytes, alphabet.symbols().as_bytes()))
+}
+
+/// Decode unpadded base32 written with a custom alphabet, which must have 32 symbols. Any aliases of the alphabet are
+/// accepted too.
+pub fn from_base32_with(s: &str, alphabet: &Alphabet) -> Result<Vec<u8>, Base32Error> {
+    check_alphabet(alphabet)?;
+    decode(s, None, |c| alphabet.value(c))
+}
+
+fn check_alphabet(alphabet: &Alphabet) -> Result<(), Base32Error> {
+    match alphabet.len() {
+        32 => Ok(()),
+        n => Err(Base32Error::InvalidAlphabet(n)),
+    }
+}
+
+/// Encode the provided bytes into unpadded base32 with the RFC 4648 alphabet, in upper or lower case
+pub(crate) fn to_base32_rfc4648(bytes: &[u8], upper_case: bool) -> String {
+    let s = encode(bytes, RFC4648_ALPHABET);
+    if upper_case {
+        s
+    } else {
+        s.to_ascii_lowercase()
+    }
+}
+
+/// Decode unpadded base32 with the RFC 4648 alphabet, in either case
+pub(crate) fn from_base32_rfc4648(s: &str) -> Result<Vec<u8>, Base32Error> {
+    decode(s, None, |c| {
+        let c = c.to_ascii_uppercase();
+        RFC4648_ALPHABET
+            .iter()
+            .position(|&a| char::from(a) == c)
+            .map(|v| v as u8)
+    })
+}
+
+fn encode(bytes: &[u8], alphabet: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() * 8 + 4) / 5);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for &b in bytes {
+        buffer = buffer << 8 | u16::from(b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(char::from(alphabet[usize::from(buffer >> bits & 0x1f)]));
+        }
+    }
+    if bits > 0 {
+        out.push(char::from(alphabet[usize::from(buffer << (5 - bits) & 0x1f)]));
+    }
+    out
+}
+
+/// Encode the provided bytes into Crockford base32 followed by a check symbol
+pub fn to_base32_check(bytes: &[u8]) -> String {
+    let mut out = to_base32(bytes);
+    out.push(check_symbol(bytes));
+    out
+}
+
+/// Decode a Crockford base32 string. Error positions are byte offsets into `s`.
+pub fn from_base32(s: &str) -> Result<Vec<u8>, Base32Error> {
+    decode(s, Some('-'), digit_value)
+}
+
+/// Decode base32 using `digit_value` to look up each character, skipping any `separator` characters
+fn decode<F>(s: &str, separator: Option<char>, digit_value: F) -> Result<Vec<u8>, Base32Error>
+where F: Fn(char) -> Option<u8> {
+    let mut out = Vec::with_capacity(s.len() * 5 / 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    let mut digits = 0;
+    for (index, character) in s.char_indices() {
+        if Some(character) == separator {
+            continue;
+        }
+        buffer =
+            buffer << 5 | u16::from(digit_value(character).ok_or(Base32Error::InvalidCharacter { index, character })?);
+        bits += 5;
+        digits += 1;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    // Leftover bits are padding, so there must be fewer than a whole character's worth and they must be zero
+    if bits >= 5 {
+        return Err(Base32Error::InvalidLength(digits));
+    }
+    if buffer & ((1 << bits) - 1) != 0 {
+        return Err(Base32Error::NonZeroPadding);
+    }
+    Ok(out)
+}
+
+/// Decode a Crockford base32 string that ends in a check symbol, as produced by [to_base32_check]
+pub fn from_base32_check(s: &str) -> Result<Vec<u8>, Base32Error> {
+    let (index, check) = s.char_indices().last().ok_or(Base32Error::InvalidLength(0))?;
+    let bytes = from_base32(&s[..index])?;
+    let value = check_value(check).ok_or(Base32Error::InvalidCharacter {
+        index,
+        character: check,
+    })?;
+    if value != checksum(&bytes) {
+        return Err(Base32Error::ChecksumMismatch);
+    }
+    Ok(bytes)
+}
+
+/// The value of a data character, applying Crockford's case and ambiguous-character folding
+fn digit_value(c: char) -> Option<u8> {
+    let c = match c.to_ascii_uppercase() {
+        'O' => '0',
+        'I' | 'L' => '1',
+        c => c,
+    };
+    ALPHABET.iter().position(|&a| char::from(a) == c).map(|v| v as u8)
+}
+
+/// The value of a check symbol, which may also be one of the extra check-only symbols
+fn check_value(c: char) -> Option<u8> {
+    digit_value(c).or_else(|| {
+        let c = c.to_ascii_uppercase();
+        CHECK_SYMBOLS
+            .iter()
+            .position(|&a| char::from(a) == c)
+            .map(|v| 32 + v as u8)
+    })
+}
+
+/// The number that the encoded symbols represent modulo 37, as Crockford specifies: the big-endian value of `bytes`,
+/// shifted left by the zero bits that pad the final symbol
+fn checksum(bytes: &[u8]) -> u8 {
+    let value = bytes.iter().fold(0u16, |acc, &b| (acc * 256 + u16::from(b)) % 37);
+    let padding = (5 - bytes.len() * 8 % 5) % 5;
+    ((value << padding) % 37) as u8
+}
+
+fn check_symbol(bytes: &[u8]) -> char {
+    let value = usize::from(checksum(bytes));
+    if value < ALPHABET.len() {
+        char::from(ALPHABET[value])
+    } else {
+        char::from(CHECK_SYMBOLS[value - ALPHABET.len()])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vectors() {
+        // The RFC 4648 test vectors, translated to the Crockford alphabet, and their check symbols. The check symbol is
+        // computed over the encoded value, so "CR" (12 * 32 + 24 = 408) has the check symbol for 408 % 37 = 1.
+        for (bytes, encoded, check) in &[
+            (&b""[..], "", '0'),
+            (b"f", "CR", '1'),
+            (b"fo", "CSQG", 'S'),
+            (b"foo", "CSQPY", 'Q'),
+            (b"foob", "CSQPYRG", 'V'),
+            (b"fooba", "CSQPYRK1", 'U'),
+            (b"foobar", "CSQPYRK1E8", 'R'),
+            (&[0xff; 5], "ZZZZZZZZ", 'F'),
+            (&[0, 0, 1], "00002", '2'),
+        ] {
+            assert_eq!(to_base32(bytes), *encoded);
+            assert_eq!(from_base32(encoded).unwrap(), *bytes);
+            let checked = format!("{}{}", encoded, check);
+            assert_eq!(to_base32_check(bytes), checked);
+            assert_eq!(from_base32_check(&checked).unwrap(), *bytes);
+        }
+    }
+
+    #[test]
+    fn rfc4648() {
+        assert_eq!(to_base32_rfc4648(b"foobar", true), "MZXW6YTBOI");
+        assert_eq!(to_base32_rfc4648(b"foobar", false), "mzxw6ytboi");
+        assert_eq!(from_base32_rfc4648("mzxW6YTBOI").unwrap(), b"foobar");
+        assert!(from_base32_rfc4648("mzxw6ytbo1").is_err());
+        assert!(from_base32_rfc4648("mzxw-6ytboi").is_err());
+    }
+
+    #[test]
+    fn human_input() {
+        assert_eq!(from_base32("csqp-yrk1").unwrap(), b"fooba");
+        assert_eq!(from_base32("CSQPYRKl").unwrap(), b"fooba");
+        assert_eq!(from_base32("CSQPYRKI").unwrap(), b"fooba");
+        assert_eq!(from_base32("0O002").unwrap(), from_base32("00002").unwrap());
+        assert_eq!(from_base32_check("csqpyrk1u").unwrap(), b"fooba");
+        assert_eq!(from_base32_check("CSQPYRK1-U").unwrap(), b"fooba");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            from_base32("CSQU"),
+            Err(Base32Error::InvalidCharacter {
+                index: 3,
+                character: 'U'
+            })
+        );
+        assert_eq!(from_base32("C"), Err(Base32Error::InvalidLength(1)));
+        assert_eq!(from_base32("CSQ"), Err(Base32Error::InvalidLength(3)));
+        assert_eq!(from_base32("CS"), Err(Base32Error::NonZeroPadding));
+        assert_eq!(from_base32_check("CSQPYRK1V"), Err(Base32Error::ChecksumMismatch));
+        // Transposing adjacent characters is caught by the check symbol
+        assert_eq!(from_base32_check("CSQPYRK16"), Err(Base32Error::ChecksumMismatch));
+        assert_eq!(from_base32_check("CSQPYR1KU"), Err(Base32Error::ChecksumMismatch));
+        assert_eq!(
+            from_base32_check("CR!"),
+            Err(Base32Error::InvalidCharacter {
+                index: 2,
+                character: '!'
+            })
+        );
+        assert_eq!(from_base32_check(""), Err(Base32Error::InvalidLength(0)));
+    }
+
+    #[test]
+    fn custom_alphabet() {
+        let alphabet = Alphabet::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ234567")
+            .unwrap()
+            .case_insensitive()
+            .unwrap();
+        assert_eq!(to_base32_with(b"foobar", &alphabet).unwrap(), "MZXW6YTBOI");
+        assert_eq!(
+            to_base32_with(b"foobar", &alphabet).unwrap(),
+            to_base32_rfc4648(b"foobar", true)
+        );
+        assert_eq!(from_base32_with("mzxw6ytboi", &alphabet).unwrap(), b"foobar");
+        assert_eq!(
+            from_base32_with("MZXW1", &alphabet),
+            Err(Base32Error::InvalidCharacter {
+                index: 4,
+                character: '1'
+            })
+        );
+    }
+
+    #[test]
+    fn wrong_alphabet_size() {
+        let digits = Alphabet::new("0123456789").unwrap();
+        assert_eq!(
+            to_base32_with(b"foobar", &digits),
+            Err(Base32Error::InvalidAlphabet(10))
+        );
+        assert_eq!(from_base32_with("00", &digits), Err(Base32Error::InvalidAlphabet(10)));
+    }
+
+    #[test]
+    fn byte_arrays() {
+        let key = [0xa5u8; 32];
+        let encoded = key.to_base32();
+        assert_eq!(encoded.len(), 52);
+        assert_eq!(<[u8; 32]>::from_base32(&encoded.to_lowercase()).unwrap(), key);
+        assert!(matches!(
+            <[u8; 32]>::from_base32("CR"),
+            Err(Base32Error::ByteArrayError(_))
+        ));
+    }
+}
@@ -0,0 +1,483 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Base58 in the block-based variant used by Monero addresses. The input is split into 8-byte blocks that each encode
+//! to 11 characters (a shorter final block encodes to proportionally fewer), so unlike Bitcoin's base58 encoding and
+//! decoding take linear time and leading zero bytes need no special treatment. The alphabet is Bitcoin's.
+//!
+//! Bitcoin's variant, which treats the whole input as one big number, is available as [to_base58_btc] and
+//! [from_base58_btc] for interoperability with other tooling.
+
+use crate::{
+    encoding::alphabet::{Alphabet, BASE58_BITCOIN},
+    ByteArray,
+};
+use thiserror::Error;
+#[cfg(feature = "tiny-keccak")]
+use tiny_keccak::{Hasher, Keccak};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Base58Error {
+    #[error("Only base58 characters are permitted, but found '{character}' at position {index}")]
+    InvalidCharacter { index: usize, character: char },
+    #[error("The final base58 block, at position {index}, can't be {size} characters long")]
+    InvalidBlockSize { index: usize, size: usize },
+    #[error("The base58 block at position {index} encodes a value too large for its size")]
+    Overflow { index: usize },
+    #[error("The checksum does not match the data. Check the value for typos")]
+    ChecksumMismatch,
+    #[error("An alphabet of {0} symbols can't be used for base58, which needs 58")]
+    InvalidAlphabet(usize),
+    #[error("{0}")]
+    ByteArrayError(#[from] crate::ByteArrayError),
+}
+
+/// Conversions to and from base58, provided for every [ByteArray]
+pub trait Base58 {
+    fn from_base58(s: &str) -> Result<Self, Base58Error>
+    where Self: Sized;
+
+    fn to_base58(&self) -> String;
+}
+
+impl<T: ByteArray> Base58 for T {
+    fn from_base58(s: &str) -> Result<Self, Base58Error> {
+        let bytes = from_base58(s)?;
+        Ok(Self::try_from_bytes(bytes)?)
+    }
+
+    fn to_base58(&self) -> String {
+        to_base58(self.as_bytes())
+    }
+}
+
+/// Checked base58, as used by Monero addresses: the data is followed by the first four bytes of its Keccak-256 hash, so
+/// that typos are caught when the string is decoded. Provided for every [ByteArray].
+#[cfg(feature = "tiny-keccak")]
+pub trait Base58Checked {
+    fn from_base58_checked(s: &str) -> Result<Self, Base58Error>
+    where Self: Sized;
+
+    fn to_base58_checked(&self) -> String;
+}
+
+#[cfg(feature = "tiny-keccak")]
+impl<T: ByteArray> Base58Checked for T {
+    fn from_base58_checked(s: &str) -> Result<Self, Base58Error> {
+        let bytes = from_base58_checked(s)?;
+        Ok(Self::try_from_bytes(bytes)?)
+    }
+
+    fn to_base58_checked(&self) -> String {
+        to_base58_checked(self.as_bytes())
+    }
+}
+
+pub(crate) const FULL_BLOCK_SIZE: usize = 8;
+pub(crate) const FULL_ENCODED_BLOCK_SIZE: usize = 11;
+/// The number of characters that a block of each size (0 to 8 bytes) encodes to
+const ENCODED_BLOCK_SIZES: [usize; FULL_BLOCK_SIZE + 1] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+
+/// Encode the provided bytes into a base58 string
+pub fn to_base58(bytes: &[u8]) -> String {
+    encode(bytes, &BASE58_BITCOIN)
+}
+
+/// Encode the provided bytes into a base58 string with a custom alphabet, which must have 58 symbols
+pub fn to_base58_with(bytes: &[u8], alphabet: &Alphabet) -> Result<String, Base58Error> {
+    check_alphabet(alphabet)?;
+    Ok(encode(bytes, alphabet))
+}
+
+fn encode(bytes: &[u8], alphabet: &Alphabet) -> String {
+    let full_blocks = bytes.len() / FULL_BLOCK_SIZE;
+    let mut out =
+        Vec::with_capacity(full_blocks * FULL_ENCODED_BLOCK_SIZE + ENCODED_BLOCK_SIZES[bytes.len() % FULL_BLOCK_SIZE]);
+    for block in bytes.chunks(FULL_BLOCK_SIZE) {
+        encode_block(block, alphabet, &mut out);
+    }
+    // Only characters from the alphabet have been written
+    String::from_utf8(out).expect("base58 output is ASCII")
+}
+
+/// Append the characters for a block of at most [FULL_BLOCK_SIZE] bytes to `out`
+pub(crate) fn encode_block(block: &[u8], alphabet: &Alphabet, out: &mut Vec<u8>) {
+    let mut num = block.iter().fold(0u64, |acc, &b| acc << 8 | u64::from(b));
+    let start = out.len();
+    out.resize(start + ENCODED_BLOCK_SIZES[block.len()], alphabet.symbol(0));
+    for c in out[start..].iter_mut().rev() {
+        *c = alphabet.symbol((num % 58) as u8);
+        num /= 58;
+    }
+}
+
+/// Decode a base58 string into bytes. Error positions are byte offsets into `s`.
+pub fn from_base58(s: &str) -> Result<Vec<u8>, Base58Error> {
+    from_base58_with(s, &BASE58_BITCOIN)
+}
+
+/// Decode a base58 string written with a custom alphabet, which must have 58 symbols
+pub fn from_base58_with(s: &str, alphabet: &Alphabet) -> Result<Vec<u8>, Base58Error> {
+    check_alphabet(alphabet)?;
+    let last_encoded = s.len() % FULL_ENCODED_BLOCK_SIZE;
+    // Check the size of the final block before doing any work
+    let last_size = decoded_block_size(last_encoded, s.len() - last_encoded)?;
+    let mut out = Vec::with_capacity(s.len() / FULL_ENCODED_BLOCK_SIZE * FULL_BLOCK_SIZE + last_size);
+    for (i, block) in s.as_bytes().chunks(FULL_ENCODED_BLOCK_SIZE).enumerate() {
+        let mut digits = [0u8; FULL_ENCODED_BLOCK_SIZE];
+        for (j, &c) in block.iter().enumerate() {
+            digits[j] = digit_value(c, i * FULL_ENCODED_BLOCK_SIZE + j, s, alphabet)?;
+        }
+        decode_block(&digits[..block.len()], i * FULL_ENCODED_BLOCK_SIZE, &mut out)?;
+    }
+    Ok(out)
+}
+
+/// Append the bytes for a block of at most [FULL_ENCODED_BLOCK_SIZE] digit values (not characters) to `out`. `index`
+/// is the position of the block in the input, for error reporting.
+pub(crate) fn decode_block(digits: &[u8], index: usize, out: &mut Vec<u8>) -> Result<(), Base58Error> {
+    let size = decoded_block_size(digits.len(), index)?;
+    let num = digits.iter().fold(0u128, |acc, &d| acc * 58 + u128::from(d));
+    if num >> (8 * size) != 0 {
+        return Err(Base58Error::Overflow { index });
+    }
+    out.extend_from_slice(&num.to_be_bytes()[16 - size..]);
+    Ok(())
+}
+
+/// The number of bytes of Keccak-256 hash appended by [to_base58_checked]
+#[cfg(feature = "tiny-keccak")]
+const CHECKSUM_SIZE: usize = 4;
+
+#[cfg(feature = "tiny-keccak")]
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(bytes);
+    keccak.finalize(&mut hash);
+    let mut checksum = [0u8; CHECKSUM_SIZE];
+    checksum.copy_from_slice(&hash[..CHECKSUM_SIZE]);
+    checksum
+}
+
+/// Encode the provided bytes into block base58 with a Keccak-256 checksum appended, as for Monero addresses
+#[cfg(feature = "tiny-keccak")]
+pub fn to_base58_checked(bytes: &[u8]) -> String {
+    let mut data = Vec::with_capacity(bytes.len() + CHECKSUM_SIZE);
+    data.extend_from_slice(bytes);
+    data.extend_from_slice(&checksum(bytes));
+    to_base58(&data)
+}
+
+/// Decode a string produced by [to_base58_checked], returning [Base58Error::ChecksumMismatch] if the checksum does
+/// not match the data
+#[cfg(feature = "tiny-keccak")]
+pub fn from_base58_checked(s: &str) -> Result<Vec<u8>, Base58Error> {
+    let mut data = from_base58(s)?;
+    if data.len() < CHECKSUM_SIZE {
+        return Err(Base58Error::ChecksumMismatch);
+    }
+    let expected = data.split_off(data.len() - CHECKSUM_SIZE);
+    if expected[..] != checksum(&data)[..] {
+        return Err(Base58Error::ChecksumMismatch);
+    }
+    Ok(data)
+}
+
+/// Encode the provided bytes with Bitcoin's base58, in which each leading zero byte becomes a `1`
+pub fn to_base58_btc(bytes: &[u8]) -> String {
+    encode_btc(bytes, &BASE58_BITCOIN)
+}
+
+/// Encode the provided bytes with Bitcoin's base58 and a custom alphabet, which must have 58 symbols, as for Ripple
+/// addresses
+pub fn to_base58_btc_with(bytes: &[u8], alphabet: &Alphabet) -> Result<String, Base58Error> {
+    check_alphabet(alphabet)?;
+    Ok(encode_btc(bytes, alphabet))
+}
+
+fn encode_btc(bytes: &[u8], alphabet: &Alphabet) -> String {
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    // The base58 digits of the number, least significant first
+    let mut digits = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for &b in &bytes[zeros..] {
+        let mut carry = u32::from(b);
+        for d in digits.iter_mut() {
+            carry += u32::from(*d) << 8;
+            *d = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut out = String::with_capacity(zeros + digits.len());
+    out.extend(std::iter::repeat(char::from(alphabet.symbol(0))).take(zeros));
+    out.extend(digits.iter().rev().map(|&d| char::from(alphabet.symbol(d))));
+    out
+}
+
+/// Decode a string encoded with Bitcoin's base58. Error positions are byte offsets into `s`.
+pub fn from_base58_btc(s: &str) -> Result<Vec<u8>, Base58Error> {
+    from_base58_btc_with(s, &BASE58_BITCOIN)
+}
+
+/// Decode a string encoded with Bitcoin's base58 and a custom alphabet, which must have 58 symbols
+pub fn from_base58_btc_with(s: &str, alphabet: &Alphabet) -> Result<Vec<u8>, Base58Error> {
+    check_alphabet(alphabet)?;
+    let zeros = s.bytes().take_while(|&c| c == alphabet.symbol(0)).count();
+    // The bytes of the number, least significant first
+    let mut bytes = Vec::with_capacity(s.len() * 733 / 1000 + 1);
+    for (index, c) in s.bytes().enumerate().skip(zeros) {
+        let mut carry = u32::from(digit_value(c, index, s, alphabet)?);
+        for b in bytes.iter_mut() {
+            carry += u32::from(*b) * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    bytes.resize(bytes.len() + zeros, 0);
+    bytes.reverse();
+    Ok(bytes)
+}
+
+fn decoded_block_size(encoded_size: usize, index: usize) -> Result<usize, Base58Error> {
+    ENCODED_BLOCK_SIZES
+        .iter()
+        .position(|&size| size == encoded_size)
+        .ok_or(Base58Error::InvalidBlockSize {
+            index,
+            size: encoded_size,
+        })
+}
+
+fn check_alphabet(alphabet: &Alphabet) -> Result<(), Base58Error> {
+    match alphabet.len() {
+        58 => Ok(()),
+        n => Err(Base58Error::InvalidAlphabet(n)),
+    }
+}
+
+pub(crate) fn digit_value(c: u8, index: usize, s: &str, alphabet: &Alphabet) -> Result<u8, Base58Error> {
+    match alphabet.value_of_byte(c) {
+        Some(v) => Ok(v),
+        None => Err(Base58Error::InvalidCharacter {
+            index,
+            // Report the whole character if `c` is the start of a multi-byte one
+            character: s
+                .get(index..)
+                .and_then(|rest| rest.chars().next())
+                .unwrap_or(std::char::REPLACEMENT_CHARACTER),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hex::from_hex;
+
+    #[test]
+    fn block_vectors() {
+        // Test vectors from Monero's base58 unit tests
+        for (hex, b58) in &[
+            ("", ""),
+            ("00", "11"),
+            ("39", "1z"),
+            ("ff", "5Q"),
+            ("0000", "111"),
+            ("0039", "11z"),
+            ("0100", "15R"),
+            ("ffff", "LUv"),
+            ("000000", "11111"),
+            ("000039", "1111z"),
+            ("010000", "11LUw"),
+            ("ffffff", "2UzHL"),
+            ("00000039", "11111z"),
+            ("ffffffff", "7YXq9G"),
+            ("0000000039", "111111z"),
+            ("ffffffffff", "VtB5VXc"),
+            ("ffffffffffff", "3CUsUpv9t"),
+            ("ffffffffffffff", "Ahg1opVcGW"),
+            ("ffffffffffffffff", "jpXCZedGfVQ"),
+            ("0000000000000000", "11111111111"),
+            ("0000000000000001", "11111111112"),
+            ("0000000000000008", "11111111119"),
+            ("0000000000000009", "1111111111A"),
+            ("000000000000003a", "11111111121"),
+            ("00ffffffffffffff", "1Ahg1opVcGW"),
+            ("06156013762879f7", "22222222222"),
+            ("05e022ba374b2a00", "1z111111111"),
+            ("06156013762879f7ffffffffff", "22222222222VtB5VXc"),
+        ] {
+            let bytes = from_hex(hex).unwrap();
+            assert_eq!(to_base58(&bytes), *b58);
+            assert_eq!(from_base58(b58).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn decode_errors() {
+        for s in &["5R", "zz", "LUw", "zzz", "2UzHM", "jpXCZedGfVR", "zzzzzzzzzzz"] {
+            assert_eq!(from_base58(s), Err(Base58Error::Overflow { index: 0 }), "{}", s);
+        }
+        assert_eq!(from_base58("11111111111zz"), Err(Base58Error::Overflow { index: 11 }));
+        for s in &["1", "z", "1111", "zzzz", "11111111"] {
+            assert_eq!(
+                from_base58(s),
+                Err(Base58Error::InvalidBlockSize {
+                    index: 0,
+                    size: s.len()
+                }),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(
+            from_base58("111111111111"),
+            Err(Base58Error::InvalidBlockSize { index: 11, size: 1 })
+        );
+        assert_eq!(
+            from_base58("11111111110"),
+            Err(Base58Error::InvalidCharacter {
+                index: 10,
+                character: '0'
+            })
+        );
+        assert_eq!(
+            from_base58("1é"),
+            Err(Base58Error::InvalidCharacter {
+                index: 1,
+                character: 'é'
+            })
+        );
+    }
+
+    #[test]
+    fn bitcoin_vectors() {
+        // Test vectors from Bitcoin Core's base58_encode_decode.json
+        for (hex, b58) in &[
+            ("", ""),
+            ("61", "2g"),
+            ("626262", "a3gV"),
+            ("636363", "aPEr"),
+            (
+                "73696d706c792061206c6f6e6720737472696e67",
+                "2cFupjhnEsSn59qHXstmK2ffpLv2",
+            ),
+            (
+                "00eb15231dfceb60925886b67d065299925915aeb172c06647",
+                "1NS17iag9jJgTHD1VXjvLCEnZuQ3rJDE9L",
+            ),
+            ("516b6fcd0f", "ABnLTmg"),
+            ("bf4f89001e670274dd", "3SEo3LWLoPntC"),
+            ("572e4794", "3EFU7m"),
+            ("ecac89cad93923c02321", "EJDM8drfXA6uyA"),
+            ("10c8511e", "Rt5zm"),
+            ("00000000000000000000", "1111111111"),
+        ] {
+            let bytes = from_hex(hex).unwrap();
+            assert_eq!(to_base58_btc(&bytes), *b58);
+            assert_eq!(from_base58_btc(b58).unwrap(), bytes);
+        }
+        assert_eq!(
+            from_base58_btc("3EFU7l"),
+            Err(Base58Error::InvalidCharacter {
+                index: 5,
+                character: 'l'
+            })
+        );
+    }
+
+    #[test]
+    fn custom_alphabets() {
+        use crate::encoding::alphabet::{BASE58_FLICKR, BASE58_RIPPLE};
+
+        let translate = |s: &str, to: &Alphabet| -> String {
+            s.chars()
+                .map(|c| char::from(to.symbol(BASE58_BITCOIN.value(c).unwrap())))
+                .collect()
+        };
+        let data = from_hex("00eb15231dfceb60925886b67d065299925915aeb172c06647").unwrap();
+        for alphabet in &[BASE58_RIPPLE, BASE58_FLICKR] {
+            let block = to_base58_with(&data, alphabet).unwrap();
+            assert_eq!(block, translate(&to_base58(&data), alphabet));
+            assert_eq!(from_base58_with(&block, alphabet).unwrap(), data);
+            let btc = to_base58_btc_with(&data, alphabet).unwrap();
+            assert_eq!(btc, translate(&to_base58_btc(&data), alphabet));
+            assert_eq!(from_base58_btc_with(&btc, alphabet).unwrap(), data);
+        }
+        // Ripple's zero digit is 'r', so Ripple addresses start with it
+        assert!(to_base58_btc_with(&data, &BASE58_RIPPLE).unwrap().starts_with('r'));
+        assert_eq!(
+            from_base58_btc_with("r0", &BASE58_RIPPLE),
+            Err(Base58Error::InvalidCharacter {
+                index: 1,
+                character: '0'
+            })
+        );
+        let hex_digits = Alphabet::new("0123456789abcdef").unwrap();
+        assert_eq!(
+            to_base58_with(&data, &hex_digits),
+            Err(Base58Error::InvalidAlphabet(16))
+        );
+        assert_eq!(
+            from_base58_btc_with("11", &hex_digits),
+            Err(Base58Error::InvalidAlphabet(16))
+        );
+    }
+
+    #[cfg(feature = "tiny-keccak")]
+    #[test]
+    fn checked() {
+        // The Monero general fund address: network byte, public spend and view keys, and checksum
+        let address = "44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A";
+        let data = from_base58_checked(address).unwrap();
+        assert_eq!(data.len(), 65);
+        assert_eq!(data[0], 0x12);
+        assert_eq!(to_base58_checked(&data), address);
+
+        let typo = address.replace("44AFF", "44AFE");
+        assert_eq!(from_base58_checked(&typo), Err(Base58Error::ChecksumMismatch));
+        assert_eq!(from_base58_checked("11"), Err(Base58Error::ChecksumMismatch));
+
+        let key = [7u8; 32];
+        assert_eq!(<[u8; 32]>::from_base58_checked(&key.to_base58_checked()).unwrap(), key);
+    }
+
+    #[test]
+    fn byte_arrays() {
+        let key = [0x5au8; 32];
+        let encoded = key.to_base58();
+        assert_eq!(encoded.len(), 44);
+        assert_eq!(<[u8; 32]>::from_base58(&encoded).unwrap(), key);
+        assert!(matches!(
+            <[u8; 32]>::from_base58("11"),
+            Err(Base58Error::ByteArrayError(_))
+        ));
+    }
+}
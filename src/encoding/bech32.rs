@@ -0,0 +1,471 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Bech32 (BIP-173) and bech32m (BIP-350), the checksummed base32 encodings used for segwit and many other chains'
+//! addresses. A string is a human-readable prefix (HRP), the separator `1`, the data and a six character checksum
+//! over both, so a mistyped prefix is caught as well as a mistyped address. Strings are written in lower case and
+//! accepted in either case, but not a mix of the two, and may be at most 90 characters long.
+//!
+//! [to_bech32] and [from_bech32] carry whole bytes. Many addresses, such as segwit's, carry 5-bit values that aren't
+//! byte-aligned instead; use [to_bech32_u5] and [from_bech32_u5] for those, or [to_segwit_address] and
+//! [from_segwit_address], which also apply the segwit rules for the witness version and program.
+
+use crate::ByteArray;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Bech32Error {
+    #[error("A bech32 string may not mix upper and lower case characters")]
+    MixedCase,
+    #[error("The separator '1' between the prefix and the data is missing")]
+    MissingSeparator,
+    #[error("The human-readable prefix must be 1 to 83 printable ASCII characters")]
+    InvalidHrp,
+    #[error("Expected the prefix '{expected}', but found '{actual}'")]
+    HrpMismatch { expected: String, actual: String },
+    #[error("Only bech32 characters are permitted, but found '{character}' at position {index}")]
+    InvalidCharacter { index: usize, character: char },
+    #[error("The bech32 string is {0} characters long, which is more than the maximum of 90")]
+    TooLong(usize),
+    #[error("The bech32 string is too short to contain a checksum")]
+    MissingChecksum,
+    #[error("The checksum does not match the data. Check the value for typos")]
+    ChecksumMismatch,
+    #[error("Expected a {expected:?} checksum, but found a {actual:?} checksum")]
+    VariantMismatch {
+        expected: Bech32Variant,
+        actual: Bech32Variant,
+    },
+    #[error("The data does not encode a whole number of bytes")]
+    InvalidPadding,
+    #[error("Data values must be less than 32, but found {value} at position {index}")]
+    InvalidU5 { index: usize, value: u8 },
+    #[error("Invalid segwit witness version {0}")]
+    InvalidWitnessVersion(u8),
+    #[error("A segwit program of {0} bytes is not valid for its witness version")]
+    InvalidProgramLength(usize),
+    #[error("{0}")]
+    ByteArrayError(#[from] crate::ByteArrayError),
+}
+
+/// The checksum constant, which is the only difference between bech32 and bech32m
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bech32Variant {
+    /// BIP-173, used for segwit version 0 addresses
+    Bech32,
+    /// BIP-350, which fixes bech32's weakness to inserted or deleted `q`s before a final `p`
+    Bech32m,
+}
+
+impl Bech32Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Bech32Variant::Bech32 => 1,
+            Bech32Variant::Bech32m => 0x2bc8_30a3,
+        }
+    }
+}
+
+/// Conversions to and from bech32 with a caller-supplied prefix, provided for every [ByteArray]
+pub trait Bech32 {
+    /// Decode `s`, which must have the prefix `hrp` (in either case) and a checksum of the given variant
+    fn from_bech32(s: &str, hrp: &str, variant: Bech32Variant) -> Result<Self, Bech32Error>
+    where Self: Sized;
+
+    fn to_bech32(&self, hrp: &str, variant: Bech32Variant) -> Result<String, Bech32Error>;
+}
+
+impl<T: ByteArray> Bech32 for T {
+    fn from_bech32(s: &str, hrp: &str, variant: Bech32Variant) -> Result<Self, Bech32Error> {
+        let (actual_hrp, bytes, actual_variant) = from_bech32(s)?;
+        if !actual_hrp.eq_ignore_ascii_case(hrp) {
+            return Err(Bech32Error::HrpMismatch {
+                expected: hrp.to_string(),
+                actual: actual_hrp,
+            });
+        }
+        if actual_variant != variant {
+            return Err(Bech32Error::VariantMismatch {
+                expected: variant,
+                actual: actual_variant,
+            });
+        }
+        Ok(Self::try_from_bytes(bytes)?)
+    }
+
+    fn to_bech32(&self, hrp: &str, variant: Bech32Variant) -> Result<String, Bech32Error> {
+        to_bech32(hrp, self.as_bytes(), variant)
+    }
+}
+
+const ALPHABET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const SEPARATOR: char = '1';
+const CHECKSUM_LENGTH: usize = 6;
+const MAX_LENGTH: usize = 90;
+const GENERATORS: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+
+/// Encode `bytes` as bech32 with the prefix `hrp`, which is written in lower case
+pub fn to_bech32(hrp: &str, bytes: &[u8], variant: Bech32Variant) -> Result<String, Bech32Error> {
+    to_bech32_u5(hrp, &to_five_bits(bytes), variant)
+}
+
+/// Encode 5-bit values, each less than 32, as bech32 with the prefix `hrp`, which is written in lower case
+pub fn to_bech32_u5(hrp: &str, values: &[u8], variant: Bech32Variant) -> Result<String, Bech32Error> {
+    if let Some((index, &value)) = values.iter().enumerate().find(|(_, &v)| v > 31) {
+        return Err(Bech32Error::InvalidU5 { index, value });
+    }
+    check_hrp(hrp)?;
+    if hrp.bytes().any(|c| c.is_ascii_lowercase()) && hrp.bytes().any(|c| c.is_ascii_uppercase()) {
+        return Err(Bech32Error::MixedCase);
+    }
+    let hrp = hrp.to_ascii_lowercase();
+    let mut data = values.to_vec();
+    let length = hrp.len() + 1 + data.len() + CHECKSUM_LENGTH;
+    if length > MAX_LENGTH {
+        return Err(Bech32Error::TooLong(length));
+    }
+    let checksum = checksum(&hrp, &data, variant);
+    data.extend_from_slice(&checksum);
+    let mut out = String::with_capacity(length);
+    out.push_str(&hrp);
+    out.push(SEPARATOR);
+    out.extend(data.iter().map(|&v| char::from(ALPHABET[usize::from(v)])));
+    Ok(out)
+}
+
+/// Decode a bech32 or bech32m string, returning its prefix in lower case, the data and which checksum it has. Error
+/// positions are byte offsets into `s`. The data must be a whole number of bytes; use [from_bech32_u5] for data that
+/// isn't.
+pub fn from_bech32(s: &str) -> Result<(String, Vec<u8>, Bech32Variant), Bech32Error> {
+    let (hrp, values, variant) = from_bech32_u5(s)?;
+    Ok((hrp, from_five_bits(&values)?, variant))
+}
+
+/// Decode a bech32 or bech32m string, returning its prefix in lower case, the 5-bit data values and which checksum it
+/// has
+pub fn from_bech32_u5(s: &str) -> Result<(String, Vec<u8>, Bech32Variant), Bech32Error> {
+    if s.len() > MAX_LENGTH {
+        return Err(Bech32Error::TooLong(s.len()));
+    }
+    if s.bytes().any(|c| c.is_ascii_lowercase()) && s.bytes().any(|c| c.is_ascii_uppercase()) {
+        return Err(Bech32Error::MixedCase);
+    }
+    let separator = s.rfind(SEPARATOR).ok_or(Bech32Error::MissingSeparator)?;
+    let hrp = s[..separator].to_ascii_lowercase();
+    check_hrp(&hrp)?;
+    let data_start = separator + 1;
+    let mut data = s[data_start..]
+        .char_indices()
+        .map(|(i, c)| {
+            let lower = c.to_ascii_lowercase();
+            ALPHABET
+                .iter()
+                .position(|&a| char::from(a) == lower)
+                .map(|v| v as u8)
+                .ok_or(Bech32Error::InvalidCharacter {
+                    index: data_start + i,
+                    character: c,
+                })
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    if data.len() < CHECKSUM_LENGTH {
+        return Err(Bech32Error::MissingChecksum);
+    }
+    let residue = polymod(&hrp, &data);
+    let variant = [Bech32Variant::Bech32, Bech32Variant::Bech32m]
+        .iter()
+        .copied()
+        .find(|v| v.constant() == residue)
+        .ok_or(Bech32Error::ChecksumMismatch)?;
+    data.truncate(data.len() - CHECKSUM_LENGTH);
+    Ok((hrp, data, variant))
+}
+
+/// Encode a segwit address (BIP-173 and BIP-350): the witness version, 0 to 16, followed by the witness program.
+/// Version 0 uses bech32 and later versions use bech32m.
+pub fn to_segwit_address(hrp: &str, version: u8, program: &[u8]) -> Result<String, Bech32Error> {
+    let variant = check_segwit(version, program.len())?;
+    let mut values = vec![version];
+    values.extend(to_five_bits(program));
+    to_bech32_u5(hrp, &values, variant)
+}
+
+/// Decode a segwit address with the prefix `hrp`, returning the witness version and program
+pub fn from_segwit_address(s: &str, hrp: &str) -> Result<(u8, Vec<u8>), Bech32Error> {
+    let (actual_hrp, values, variant) = from_bech32_u5(s)?;
+    if !actual_hrp.eq_ignore_ascii_case(hrp) {
+        return Err(Bech32Error::HrpMismatch {
+            expected: hrp.to_string(),
+            actual: actual_hrp,
+        });
+    }
+    let (&version, program) = values.split_first().ok_or(Bech32Error::InvalidProgramLength(0))?;
+    let program = from_five_bits(program)?;
+    let expected = check_segwit(version, program.len())?;
+    if variant != expected {
+        return Err(Bech32Error::VariantMismatch {
+            expected,
+            actual: variant,
+        });
+    }
+    Ok((version, program))
+}
+
+/// Check a witness version and program length, returning the checksum variant the version uses
+fn check_segwit(version: u8, program_length: usize) -> Result<Bech32Variant, Bech32Error> {
+    match (version, program_length) {
+        (0, 20) | (0, 32) => Ok(Bech32Variant::Bech32),
+        (0, n) => Err(Bech32Error::InvalidProgramLength(n)),
+        (1..=16, 2..=40) => Ok(Bech32Variant::Bech32m),
+        (1..=16, n) => Err(Bech32Error::InvalidProgramLength(n)),
+        (v, _) => Err(Bech32Error::InvalidWitnessVersion(v)),
+    }
+}
+
+fn check_hrp(hrp: &str) -> Result<(), Bech32Error> {
+    if hrp.is_empty() || hrp.len() > 83 || hrp.bytes().any(|c| c < 33 || c > 126) {
+        return Err(Bech32Error::InvalidHrp);
+    }
+    Ok(())
+}
+
+/// The BCH code residue of the prefix and data, which is the variant's constant for a valid checksum
+fn polymod(hrp: &str, data: &[u8]) -> u32 {
+    let expanded = hrp
+        .bytes()
+        .map(|c| c >> 5)
+        .chain(Some(0))
+        .chain(hrp.bytes().map(|c| c & 0x1f))
+        .chain(data.iter().copied());
+    expanded.fold(1u32, |chk, v| {
+        let top = chk >> 25;
+        let chk = (chk & 0x01ff_ffff) << 5 ^ u32::from(v);
+        GENERATORS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| top >> i & 1 == 1)
+            .fold(chk, |chk, (_, g)| chk ^ g)
+    })
+}
+
+fn checksum(hrp: &str, data: &[u8], variant: Bech32Variant) -> [u8; CHECKSUM_LENGTH] {
+    let mut values = data.to_vec();
+    values.extend_from_slice(&[0; CHECKSUM_LENGTH]);
+    let residue = polymod(hrp, &values) ^ variant.constant();
+    let mut checksum = [0u8; CHECKSUM_LENGTH];
+    for (i, v) in checksum.iter_mut().enumerate() {
+        *v = (residue >> (5 * (5 - i)) & 0x1f) as u8;
+    }
+    checksum
+}
+
+/// Split bytes into 5-bit values, padding the last one with zero bits
+fn to_five_bits(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity((bytes.len() * 8 + 4) / 5);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for &b in bytes {
+        buffer = buffer << 8 | u16::from(b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push((buffer >> bits & 0x1f) as u8);
+        }
+    }
+    if bits > 0 {
+        out.push((buffer << (5 - bits) & 0x1f) as u8);
+    }
+    out
+}
+
+/// Join 5-bit values into bytes. Fewer than five bits, all zero, may be left over.
+fn from_five_bits(values: &[u8]) -> Result<Vec<u8>, Bech32Error> {
+    let mut out = Vec::with_capacity(values.len() * 5 / 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for &v in values {
+        buffer = (buffer << 5 | u16::from(v)) & 0x0fff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+        return Err(Bech32Error::InvalidPadding);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hex::from_hex;
+
+    #[test]
+    fn bip_vectors() {
+        let (hrp, data, variant) = from_bech32("A12UEL5L").unwrap();
+        assert_eq!((hrp.as_str(), data.len(), variant), ("a", 0, Bech32Variant::Bech32));
+        let (hrp, data, variant) = from_bech32("a1lqfn3a").unwrap();
+        assert_eq!((hrp.as_str(), data.len(), variant), ("a", 0, Bech32Variant::Bech32m));
+
+        let bytes = from_hex("00443214c74254b635cf84653a56d7c675be77df").unwrap();
+        let s = "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw";
+        assert_eq!(to_bech32("abcdef", &bytes, Bech32Variant::Bech32).unwrap(), s);
+        assert_eq!(
+            from_bech32(s).unwrap(),
+            ("abcdef".to_string(), bytes, Bech32Variant::Bech32)
+        );
+    }
+
+    #[test]
+    fn five_bit_values() {
+        let s = to_bech32_u5("a", &[0, 31, 7], Bech32Variant::Bech32m).unwrap();
+        assert_eq!(
+            from_bech32_u5(&s).unwrap(),
+            ("a".to_string(), vec![0, 31, 7], Bech32Variant::Bech32m)
+        );
+        assert_eq!(from_bech32(&s), Err(Bech32Error::InvalidPadding));
+        assert_eq!(
+            to_bech32_u5("a", &[0, 32], Bech32Variant::Bech32),
+            Err(Bech32Error::InvalidU5 { index: 1, value: 32 })
+        );
+    }
+
+    #[test]
+    fn segwit() {
+        let program = from_hex("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        let v0 = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+        assert_eq!(
+            from_segwit_address(&v0.to_ascii_uppercase(), "bc").unwrap(),
+            (0, program.clone())
+        );
+        assert_eq!(to_segwit_address("bc", 0, &program).unwrap(), v0);
+
+        let program = [&program[..], &program[..]].concat();
+        let v1 = "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y";
+        assert_eq!(from_segwit_address(v1, "bc").unwrap(), (1, program.clone()));
+        assert_eq!(to_segwit_address("bc", 1, &program).unwrap(), v1);
+
+        // BIP-350 invalid vectors: a version 0 address with a bech32m checksum, and a version 1 one with bech32
+        assert_eq!(
+            from_segwit_address("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh", "bc"),
+            Err(Bech32Error::VariantMismatch {
+                expected: Bech32Variant::Bech32,
+                actual: Bech32Variant::Bech32m
+            })
+        );
+        assert_eq!(
+            from_segwit_address("bc1p38j9r5y49hruaue7wxjce0updqjuyyx0kh56v8s25huc6995vvpql3jow4", "bc"),
+            Err(Bech32Error::InvalidCharacter {
+                index: 59,
+                character: 'o'
+            })
+        );
+        assert_eq!(from_segwit_address(v0, "tb").unwrap_err(), Bech32Error::HrpMismatch {
+            expected: "tb".to_string(),
+            actual: "bc".to_string()
+        });
+        assert_eq!(
+            to_segwit_address("bc", 0, &[0; 21]),
+            Err(Bech32Error::InvalidProgramLength(21))
+        );
+        assert_eq!(
+            to_segwit_address("bc", 1, &[0; 41]),
+            Err(Bech32Error::InvalidProgramLength(41))
+        );
+        assert_eq!(
+            to_segwit_address("bc", 17, &[0; 20]),
+            Err(Bech32Error::InvalidWitnessVersion(17))
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let key = [7u8; 32];
+        for &variant in &[Bech32Variant::Bech32, Bech32Variant::Bech32m] {
+            let s = key.to_bech32("tari", variant).unwrap();
+            assert!(s.starts_with("tari1"));
+            assert_eq!(<[u8; 32]>::from_bech32(&s, "tari", variant).unwrap(), key);
+            assert_eq!(
+                <[u8; 32]>::from_bech32(&s.to_ascii_uppercase(), "TARI", variant).unwrap(),
+                key
+            );
+        }
+        assert_eq!(
+            vec![1u8; 60].to_bech32("a", Bech32Variant::Bech32m),
+            Err(Bech32Error::TooLong(1 + 1 + 96 + 6))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let s = vec![1u8, 2, 3].to_bech32("tari", Bech32Variant::Bech32m).unwrap();
+        let decode = |s: &str| Vec::<u8>::from_bech32(s, "tari", Bech32Variant::Bech32m).err();
+        assert_eq!(decode(&s), None);
+        assert_eq!(
+            Vec::<u8>::from_bech32(&s, "tari", Bech32Variant::Bech32),
+            Err(Bech32Error::VariantMismatch {
+                expected: Bech32Variant::Bech32,
+                actual: Bech32Variant::Bech32m
+            })
+        );
+        assert_eq!(
+            Vec::<u8>::from_bech32(&s, "bc", Bech32Variant::Bech32m),
+            Err(Bech32Error::HrpMismatch {
+                expected: "bc".to_string(),
+                actual: "tari".to_string()
+            })
+        );
+        let mut typo = s.clone().into_bytes();
+        typo[6] = if typo[6] == b'q' { b'p' } else { b'q' };
+        assert_eq!(
+            decode(std::str::from_utf8(&typo).unwrap()),
+            Some(Bech32Error::ChecksumMismatch)
+        );
+        assert_eq!(decode(&s.replacen("tari", "TARI", 1)), Some(Bech32Error::MixedCase));
+        assert_eq!(
+            decode("tari1qqqqqbqq"),
+            Some(Bech32Error::InvalidCharacter {
+                index: 10,
+                character: 'b'
+            })
+        );
+        assert_eq!(decode("tariqqqqqq"), Some(Bech32Error::MissingSeparator));
+        assert_eq!(decode("1qqqqqq"), Some(Bech32Error::InvalidHrp));
+        assert_eq!(decode("tari1qqqq"), Some(Bech32Error::MissingChecksum));
+        assert_eq!(to_bech32("", &[], Bech32Variant::Bech32), Err(Bech32Error::InvalidHrp));
+        assert_eq!(
+            to_bech32("Tari", &[], Bech32Variant::Bech32),
+            Err(Bech32Error::MixedCase)
+        );
+        // A single 5-bit value is too short to hold a byte, so can't be padding
+        let one = checksum("a", &[0], Bech32Variant::Bech32);
+        let s = format!(
+            "a1q{}",
+            one.iter()
+                .map(|&v| char::from(ALPHABET[usize::from(v)]))
+                .collect::<String>()
+        );
+        assert_eq!(from_bech32(&s), Err(Bech32Error::InvalidPadding));
+    }
+}
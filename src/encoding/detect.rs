@@ -0,0 +1,102 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Decode text in whichever encoding it turns out to be in, for CLI tools and log scrapers that accept whatever the
+//! user pasted. The encodings are tried in a fixed order, so input that is valid in more than one of them (e.g. `abcd`
+//! is valid hex, base58 and base64) always decodes the same way.
+
+use crate::{encoding::base58, hex};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DecodeAnyError {
+    #[error("The input is not valid hex, base58 or base64")]
+    Unrecognized,
+}
+
+/// The encodings tried by [decode_any], in the order they are tried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// Hex in either case, with or without a `0x` prefix
+    Hex,
+    /// Block base58, as produced by [base58::to_base58]
+    Base58,
+    /// Standard base64, with or without padding
+    Base64,
+    /// URL-safe base64, with or without padding
+    Base64Url,
+}
+
+/// Decode `s` as hex, base58, standard base64 or URL-safe base64, whichever succeeds first, and report which it was.
+/// Whitespace around the input is ignored.
+///
+/// ```
+/// use tari_utilities::encoding::{decode_any, Encoding};
+///
+/// assert_eq!(decode_any("0xdead").unwrap(), (Encoding::Hex, vec![0xde, 0xad]));
+/// assert_eq!(decode_any(" 3yZe7d ").unwrap(), (Encoding::Base58, b"test".to_vec()));
+/// assert_eq!(decode_any("3q0=").unwrap(), (Encoding::Base64, vec![0xde, 0xad]));
+/// ```
+pub fn decode_any(s: &str) -> Result<(Encoding, Vec<u8>), DecodeAnyError> {
+    let s = s.trim();
+    if let Ok(bytes) = hex::from_hex(s) {
+        return Ok((Encoding::Hex, bytes));
+    }
+    if let Ok(bytes) = base58::from_base58(s) {
+        return Ok((Encoding::Base58, bytes));
+    }
+    if let Ok(bytes) = base64::decode_config(s, base64::STANDARD) {
+        return Ok((Encoding::Base64, bytes));
+    }
+    if let Ok(bytes) = base64::decode_config(s, base64::URL_SAFE) {
+        return Ok((Encoding::Base64Url, bytes));
+    }
+    Err(DecodeAnyError::Unrecognized)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encoding::base58::to_base58;
+
+    #[test]
+    fn detection_order() {
+        let data = [0xfb, 0xff, 0x00, 0x10, 0x7e];
+        assert_eq!(decode_any(&hex::to_hex(&data)).unwrap(), (Encoding::Hex, data.to_vec()));
+        assert_eq!(
+            decode_any(&to_base58(&data)).unwrap(),
+            (Encoding::Base58, data.to_vec())
+        );
+        let standard = base64::encode_config(&data, base64::STANDARD);
+        assert_eq!(decode_any(&standard).unwrap(), (Encoding::Base64, data.to_vec()));
+        let url_safe = base64::encode_config(&data, base64::URL_SAFE_NO_PAD);
+        assert_eq!(decode_any(&url_safe).unwrap(), (Encoding::Base64Url, data.to_vec()));
+        // Valid in every encoding, so hex wins
+        assert_eq!(decode_any("abcd").unwrap(), (Encoding::Hex, vec![0xab, 0xcd]));
+    }
+
+    #[test]
+    fn unrecognized() {
+        assert_eq!(decode_any("not valid!"), Err(DecodeAnyError::Unrecognized));
+        assert_eq!(decode_any("@abc"), Err(DecodeAnyError::Unrecognized));
+    }
+}
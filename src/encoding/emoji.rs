@@ -0,0 +1,139 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Emoji encoding, in the style of Tari emoji IDs, which maps each byte to one of 256 emoji so that values such as
+//! public keys can be shown to people in a form that is easier to compare at a glance than hex.
+
+use crate::ByteArray;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum EmojiError {
+    #[error("'{character}' at position {index} is not in the emoji alphabet")]
+    InvalidEmoji { index: usize, character: char },
+    #[error("{0}")]
+    ByteArrayError(#[from] crate::ByteArrayError),
+}
+
+/// Conversions to and from emoji strings, provided for every [ByteArray]
+pub trait EmojiEncoding {
+    fn from_emoji_string(s: &str) -> Result<Self, EmojiError>
+    where Self: Sized;
+
+    fn to_emoji_string(&self) -> String;
+}
+
+impl<T: ByteArray> EmojiEncoding for T {
+    fn from_emoji_string(s: &str) -> Result<Self, EmojiError> {
+        let bytes = from_emoji(s)?;
+        Ok(Self::try_from_bytes(bytes)?)
+    }
+
+    fn to_emoji_string(&self) -> String {
+        to_emoji(self.as_bytes())
+    }
+}
+
+/// The emoji for each byte value. They are all single code points that are displayed as emoji by default, and are in
+/// code point order so that they can be looked up with a binary search.
+#[rustfmt::skip]
+const EMOJI: [char; 256] = [
+    '🍅', '🍆', '🍇', '🍈', '🍉', '🍊', '🍋', '🍌', '🍍', '🍎', '🍏', '🍐', '🍑', '🍒', '🍓', '🍔',
+    '🍕', '🍖', '🍗', '🍘', '🍙', '🍚', '🍛', '🍜', '🍝', '🍞', '🍟', '🍠', '🍡', '🍢', '🍣', '🍤',
+    '🍥', '🍦', '🍧', '🍨', '🍩', '🍪', '🍫', '🍬', '🍭', '🍮', '🍯', '🍰', '🍱', '🍲', '🍳', '🍴',
+    '🍵', '🍶', '🍷', '🍸', '🍹', '🍺', '🍻', '🍼', '🎀', '🎁', '🎂', '🎃', '🎄', '🎅', '🎆', '🎇',
+    '🎈', '🎉', '🎊', '🎋', '🎌', '🎍', '🎎', '🎏', '🎐', '🎑', '🎒', '🎓', '🎠', '🎡', '🎢', '🎣',
+    '🎤', '🎥', '🎦', '🎧', '🎨', '🎩', '🎪', '🎫', '🎬', '🎭', '🎮', '🎯', '🎰', '🎱', '🎲', '🎳',
+    '🎴', '🎵', '🎶', '🎷', '🎸', '🎹', '🎺', '🎻', '🎼', '🎽', '🎾', '🎿', '🏀', '🏁', '🏂', '🏃',
+    '🏄', '🏠', '🏡', '🏢', '🏣', '🏤', '🏥', '🏦', '🏧', '🏨', '🏩', '🏪', '🏫', '🏬', '🏭', '🏮',
+    '🏯', '🏰', '🐀', '🐁', '🐂', '🐃', '🐄', '🐅', '🐆', '🐇', '🐈', '🐉', '🐊', '🐋', '🐌', '🐍',
+    '🐎', '🐏', '🐐', '🐑', '🐒', '🐓', '🐔', '🐕', '🐖', '🐗', '🐘', '🐙', '🐚', '🐛', '🐜', '🐝',
+    '🐞', '🐟', '🐠', '🐡', '🐢', '🐣', '🐤', '🐥', '🐦', '🐧', '🐨', '🐩', '🐪', '🐫', '🐬', '🐭',
+    '🐮', '🐯', '🐰', '🐱', '🐲', '🐳', '🐴', '🐵', '🐶', '🐷', '🐸', '🐹', '🐺', '🐻', '🐼', '🐽',
+    '🐾', '🚀', '🚁', '🚂', '🚃', '🚄', '🚅', '🚆', '🚇', '🚈', '🚉', '🚊', '🚋', '🚌', '🚍', '🚎',
+    '🚏', '🚐', '🚑', '🚒', '🚓', '🚔', '🚕', '🚖', '🚗', '🚘', '🚙', '🚚', '🚛', '🚜', '🚝', '🚞',
+    '🚟', '🚠', '🚡', '🚢', '🚣', '🚤', '🚥', '🚦', '🚧', '🚨', '🚩', '🚪', '🚫', '🚬', '🚭', '🚮',
+    '🚯', '🚰', '🚱', '🚲', '🚳', '🚴', '🚵', '🚶', '🚷', '🚸', '🚹', '🚺', '🚻', '🚼', '🚽', '🚾',
+];
+
+/// Encode each of the provided bytes as an emoji
+pub fn to_emoji(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| EMOJI[usize::from(b)]).collect()
+}
+
+/// Decode a string of emoji into bytes. Error positions are byte offsets into `s`.
+pub fn from_emoji(s: &str) -> Result<Vec<u8>, EmojiError> {
+    s.char_indices()
+        .map(|(index, character)| {
+            EMOJI
+                .binary_search(&character)
+                .map(|v| v as u8)
+                .map_err(|_| EmojiError::InvalidEmoji { index, character })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn alphabet() {
+        assert!(EMOJI.windows(2).all(|w| w[0] < w[1]));
+        for b in 0..=255u8 {
+            assert_eq!(from_emoji(&to_emoji(&[b])).unwrap(), vec![b]);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(to_emoji(&[0, 1, 255]), "🍅🍆🚾");
+        assert_eq!(to_emoji(&[]), "");
+        let key = [0x3cu8; 32];
+        let s = key.to_emoji_string();
+        assert_eq!(s.chars().count(), 32);
+        assert_eq!(<[u8; 32]>::from_emoji_string(&s).unwrap(), key);
+        assert!(matches!(
+            <[u8; 32]>::from_emoji_string("🍅"),
+            Err(EmojiError::ByteArrayError(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_glyphs() {
+        assert_eq!(
+            from_emoji("🍅🍆x"),
+            Err(EmojiError::InvalidEmoji {
+                index: 8,
+                character: 'x'
+            })
+        );
+        // A skin tone modifier is not part of the alphabet
+        assert_eq!(
+            from_emoji("🏃\u{1f3fd}"),
+            Err(EmojiError::InvalidEmoji {
+                index: 4,
+                character: '\u{1f3fd}'
+            })
+        );
+    }
+}
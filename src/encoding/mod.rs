@@ -0,0 +1,46 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Text encodings for binary data other than hex, for identifiers and addresses that people read, copy and type.
+
+pub mod alphabet;
+pub mod base32;
+pub mod base58;
+pub mod bech32;
+pub mod detect;
+pub mod emoji;
+pub mod multibase;
+pub mod percent;
+pub mod stream;
+
+#[cfg(feature = "tiny-keccak")]
+pub use self::base58::Base58Checked;
+pub use self::{
+    alphabet::{Alphabet, AlphabetError},
+    base32::{Base32, Base32Error},
+    base58::{Base58, Base58Error},
+    bech32::{Bech32, Bech32Error, Bech32Variant},
+    detect::{decode_any, DecodeAnyError, Encoding},
+    emoji::{EmojiEncoding, EmojiError},
+    multibase::MultibaseError,
+    percent::{percent_decode, percent_encode, PercentError},
+};
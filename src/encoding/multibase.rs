@@ -0,0 +1,185 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! [Multibase](https://github.com/multiformats/multibase) encoding, which prefixes the encoded data with a character
+//! identifying the base so that values are self-describing when exchanged with IPFS-adjacent tooling.
+
+use crate::{
+    encoding::{base32, base58, Base32Error, Base58Error},
+    hex::{self, HexError},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MultibaseError {
+    #[error("A multibase string can't be empty")]
+    Empty,
+    #[error("'{0}' is not a supported multibase prefix")]
+    UnknownPrefix(char),
+    #[error("{0}")]
+    HexError(#[from] HexError),
+    #[error("{0}")]
+    Base32Error(#[from] Base32Error),
+    #[error("{0}")]
+    Base58Error(#[from] Base58Error),
+    #[error("{0}")]
+    Base64Error(#[from] base64::DecodeError),
+}
+
+/// The supported multibase encodings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Base {
+    /// Lowercase hex, prefix `f`
+    Base16,
+    /// Uppercase hex, prefix `F`
+    Base16Upper,
+    /// Unpadded lowercase RFC 4648 base32, prefix `b`
+    Base32,
+    /// Unpadded uppercase RFC 4648 base32, prefix `B`
+    Base32Upper,
+    /// Bitcoin's base58, prefix `z`
+    Base58Btc,
+    /// Unpadded standard base64, prefix `m`
+    Base64,
+    /// Padded standard base64, prefix `M`
+    Base64Pad,
+    /// Unpadded URL-safe base64, prefix `u`
+    Base64Url,
+    /// Padded URL-safe base64, prefix `U`
+    Base64UrlPad,
+}
+
+impl Base {
+    /// The character that identifies this base
+    pub fn prefix(self) -> char {
+        match self {
+            Base::Base16 => 'f',
+            Base::Base16Upper => 'F',
+            Base::Base32 => 'b',
+            Base::Base32Upper => 'B',
+            Base::Base58Btc => 'z',
+            Base::Base64 => 'm',
+            Base::Base64Pad => 'M',
+            Base::Base64Url => 'u',
+            Base::Base64UrlPad => 'U',
+        }
+    }
+
+    /// The base identified by `prefix`, if it is supported
+    pub fn from_prefix(prefix: char) -> Option<Base> {
+        Some(match prefix {
+            'f' => Base::Base16,
+            'F' => Base::Base16Upper,
+            'b' => Base::Base32,
+            'B' => Base::Base32Upper,
+            'z' => Base::Base58Btc,
+            'm' => Base::Base64,
+            'M' => Base::Base64Pad,
+            'u' => Base::Base64Url,
+            'U' => Base::Base64UrlPad,
+            _ => return None,
+        })
+    }
+
+    /// Encode `bytes` in this base, without the prefix
+    pub fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            Base::Base16 => hex::to_hex(bytes),
+            Base::Base16Upper => hex::to_hex_upper(bytes),
+            Base::Base32 => base32::to_base32_rfc4648(bytes, false),
+            Base::Base32Upper => base32::to_base32_rfc4648(bytes, true),
+            Base::Base58Btc => base58::to_base58_btc(bytes),
+            Base::Base64 => base64::encode_config(bytes, base64::STANDARD_NO_PAD),
+            Base::Base64Pad => base64::encode_config(bytes, base64::STANDARD),
+            Base::Base64Url => base64::encode_config(bytes, base64::URL_SAFE_NO_PAD),
+            Base::Base64UrlPad => base64::encode_config(bytes, base64::URL_SAFE),
+        }
+    }
+
+    /// Decode `s`, which must not include the prefix, from this base
+    pub fn decode(self, s: &str) -> Result<Vec<u8>, MultibaseError> {
+        Ok(match self {
+            Base::Base16 | Base::Base16Upper => hex::from_hex(s)?,
+            Base::Base32 | Base::Base32Upper => base32::from_base32_rfc4648(s)?,
+            Base::Base58Btc => base58::from_base58_btc(s)?,
+            Base::Base64 => base64::decode_config(s, base64::STANDARD_NO_PAD)?,
+            Base::Base64Pad => base64::decode_config(s, base64::STANDARD)?,
+            Base::Base64Url => base64::decode_config(s, base64::URL_SAFE_NO_PAD)?,
+            Base::Base64UrlPad => base64::decode_config(s, base64::URL_SAFE)?,
+        })
+    }
+}
+
+/// Encode `bytes` in the given base, with the multibase prefix
+pub fn encode(base: Base, bytes: &[u8]) -> String {
+    let mut s = base.prefix().to_string();
+    s.push_str(&base.encode(bytes));
+    s
+}
+
+/// Decode a multibase string, returning the base that its prefix identified along with the data
+pub fn decode(s: &str) -> Result<(Base, Vec<u8>), MultibaseError> {
+    let prefix = s.chars().next().ok_or(MultibaseError::Empty)?;
+    let base = Base::from_prefix(prefix).ok_or(MultibaseError::UnknownPrefix(prefix))?;
+    let bytes = base.decode(&s[prefix.len_utf8()..])?;
+    Ok((base, bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spec_vectors() {
+        // From the multibase test suite
+        let data = b"yes mani !";
+        for (base, encoded) in &[
+            (Base::Base16, "f796573206d616e692021"),
+            (Base::Base16Upper, "F796573206D616E692021"),
+            (Base::Base32, "bpfsxgidnmfxgsibb"),
+            (Base::Base32Upper, "BPFSXGIDNMFXGSIBB"),
+            (Base::Base58Btc, "z7paNL19xttacUY"),
+            (Base::Base64, "meWVzIG1hbmkgIQ"),
+            (Base::Base64Pad, "MeWVzIG1hbmkgIQ=="),
+            (Base::Base64Url, "ueWVzIG1hbmkgIQ"),
+            (Base::Base64UrlPad, "UeWVzIG1hbmkgIQ=="),
+        ] {
+            assert_eq!(encode(*base, data), *encoded);
+            let (decoded_base, bytes) = decode(encoded).unwrap();
+            assert_eq!(decoded_base, *base);
+            assert_eq!(bytes, data);
+            assert_eq!(Base::from_prefix(base.prefix()), Some(*base));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(decode(""), Err(MultibaseError::Empty)));
+        assert!(matches!(decode("Q1234"), Err(MultibaseError::UnknownPrefix('Q'))));
+        assert!(matches!(decode("é"), Err(MultibaseError::UnknownPrefix('é'))));
+        assert!(matches!(decode("f0g"), Err(MultibaseError::HexError(_))));
+        assert!(matches!(decode("z0"), Err(MultibaseError::Base58Error(_))));
+        assert!(matches!(decode("b1"), Err(MultibaseError::Base32Error(_))));
+        assert!(matches!(decode("m!!"), Err(MultibaseError::Base64Error(_))));
+        assert_eq!(decode("f").unwrap(), (Base::Base16, vec![]));
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Percent-encoding (RFC 3986) for embedding arbitrary bytes, such as hex or base58 blobs, in URIs and deep links.
+//! Which ASCII characters are escaped is chosen with an [AsciiSet]; bytes outside ASCII are always escaped.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PercentError {
+    #[error("Invalid percent escape at index {0}")]
+    InvalidEscape(usize),
+}
+
+/// A set of ASCII characters to percent-encode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsciiSet {
+    mask: [u32; 4],
+}
+
+impl AsciiSet {
+    /// The empty set
+    pub const EMPTY: AsciiSet = AsciiSet { mask: [0; 4] };
+
+    /// Return a copy of this set with `byte` added, which must be ASCII
+    pub const fn add(&self, byte: u8) -> AsciiSet {
+        self.with_bit(byte, true)
+    }
+
+    /// Return a copy of this set with `byte` removed, which must be ASCII
+    pub const fn remove(&self, byte: u8) -> AsciiSet {
+        self.with_bit(byte, false)
+    }
+
+    /// Whether this set contains `byte`. Bytes outside ASCII are always encoded, so are always in the set.
+    pub fn contains(&self, byte: u8) -> bool {
+        byte >= 0x80 || self.mask[byte as usize / 32] & (1 << (byte % 32)) != 0
+    }
+
+    // Written without branches or mutation so that it can be a const fn
+    const fn with_bit(&self, byte: u8, set: bool) -> AsciiSet {
+        let word = (byte / 32) as usize;
+        let bit = 1u32 << (byte % 32);
+        let on = bit * set as u32;
+        AsciiSet {
+            mask: [
+                (self.mask[0] & !(bit * (word == 0) as u32)) | (on * (word == 0) as u32),
+                (self.mask[1] & !(bit * (word == 1) as u32)) | (on * (word == 1) as u32),
+                (self.mask[2] & !(bit * (word == 2) as u32)) | (on * (word == 2) as u32),
+                (self.mask[3] & !(bit * (word == 3) as u32)) | (on * (word == 3) as u32),
+            ],
+        }
+    }
+}
+
+/// The C0 control characters and DEL
+pub const CONTROLS: AsciiSet = AsciiSet {
+    mask: [0xffff_ffff, 0, 0, 0x8000_0000],
+};
+
+/// Everything except ASCII letters and digits
+pub const NON_ALPHANUMERIC: AsciiSet = AsciiSet {
+    mask: [0xffff_ffff, 0xfc00_ffff, 0xf800_0001, 0xf800_0001],
+};
+
+/// Everything except the RFC 3986 unreserved characters (letters, digits, `-`, `.`, `_` and `~`), which is safe for
+/// any URI component, including query parameters
+pub const COMPONENT: AsciiSet = NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// Percent-encode the characters of `bytes` that are in `set`
+pub fn percent_encode(bytes: &[u8], set: &AsciiSet) -> String {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    let mut encoded = String::with_capacity(bytes.len());
+    for &b in bytes {
+        if set.contains(b) {
+            encoded.push('%');
+            encoded.push(HEX[(b >> 4) as usize] as char);
+            encoded.push(HEX[(b & 0xf) as usize] as char);
+        } else {
+            encoded.push(b as char);
+        }
+    }
+    encoded
+}
+
+/// Decode every `%XX` escape in `s`. A `%` that is not followed by two hex digits is an error.
+pub fn percent_decode(s: &str) -> Result<Vec<u8>, PercentError> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let escape = bytes.get(i + 1..i + 3).ok_or(PercentError::InvalidEscape(i))?;
+            let high = hex_digit(escape[0]).ok_or(PercentError::InvalidEscape(i))?;
+            let low = hex_digit(escape[1]).ok_or(PercentError::InvalidEscape(i))?;
+            decoded.push(high << 4 | low);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(decoded)
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sets() {
+        for b in 0..=255u8 {
+            let c = b as char;
+            assert_eq!(CONTROLS.contains(b), c.is_ascii_control() || b >= 0x80, "{}", b);
+            assert_eq!(NON_ALPHANUMERIC.contains(b), !c.is_ascii_alphanumeric(), "{}", b);
+            assert_eq!(
+                COMPONENT.contains(b),
+                !(c.is_ascii_alphanumeric() || "-._~".contains(c)),
+                "{}",
+                b
+            );
+        }
+        let set = AsciiSet::EMPTY.add(b'/').add(b'?');
+        assert!(set.contains(b'/') && set.contains(b'?') && set.contains(0xff));
+        assert!(!set.contains(b'a') && !set.remove(b'/').contains(b'/'));
+    }
+
+    #[test]
+    fn round_trip() {
+        let link = "tari://pay?to=5Hh4 ümlaut&amount=1%";
+        let encoded = percent_encode(link.as_bytes(), &COMPONENT);
+        assert_eq!(encoded, "tari%3A%2F%2Fpay%3Fto%3D5Hh4%20%C3%BCmlaut%26amount%3D1%25");
+        assert_eq!(percent_decode(&encoded).unwrap(), link.as_bytes());
+        assert_eq!(percent_encode(b"a b/c", &CONTROLS), "a b/c");
+        assert_eq!(percent_decode("a+b%2fc").unwrap(), b"a+b/c");
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        assert_eq!(percent_decode("abc%"), Err(PercentError::InvalidEscape(3)));
+        assert_eq!(percent_decode("%4"), Err(PercentError::InvalidEscape(0)));
+        assert_eq!(percent_decode("ab%4g"), Err(PercentError::InvalidEscape(2)));
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Incremental base58 and base64 encoders and decoders, for payloads such as backups that are too large to hold in
+//! memory in both their binary and text forms. Data is pushed in with `update`, which appends whatever output is
+//! complete, and `finish` flushes the final partial block.
+//!
+//! ```
+//! use tari_utilities::encoding::{base58::to_base58, stream::Base58Encoder};
+//!
+//! let data = [7u8; 100];
+//! let mut encoder = Base58Encoder::new();
+//! let mut out = String::new();
+//! for chunk in data.chunks(30) {
+//!     encoder.update(chunk, &mut out);
+//! }
+//! encoder.finish(&mut out);
+//! assert_eq!(out, to_base58(&data));
+//! ```
+
+use crate::encoding::{
+    alphabet::BASE58_BITCOIN,
+    base58::{self, Base58Error, FULL_BLOCK_SIZE, FULL_ENCODED_BLOCK_SIZE},
+};
+
+/// Encodes base58 in the block-based format of [base58::to_base58]
+#[derive(Debug, Clone, Default)]
+pub struct Base58Encoder {
+    block: Vec<u8>,
+}
+
+impl Base58Encoder {
+    pub fn new() -> Self {
+        Base58Encoder {
+            block: Vec::with_capacity(FULL_BLOCK_SIZE),
+        }
+    }
+
+    /// Encode `bytes`, appending the characters for each block that is completed to `out`
+    pub fn update(&mut self, mut bytes: &[u8], out: &mut String) {
+        let mut buf = Vec::new();
+        while !bytes.is_empty() {
+            let n = (FULL_BLOCK_SIZE - self.block.len()).min(bytes.len());
+            self.block.extend_from_slice(&bytes[..n]);
+            bytes = &bytes[n..];
+            if self.block.len() == FULL_BLOCK_SIZE {
+                base58::encode_block(&self.block, &BASE58_BITCOIN, &mut buf);
+                self.block.clear();
+            }
+        }
+        push_ascii(&buf, out);
+    }
+
+    /// Append the characters for the final, partial block to `out`
+    pub fn finish(self, out: &mut String) {
+        let mut buf = Vec::new();
+        base58::encode_block(&self.block, &BASE58_BITCOIN, &mut buf);
+        push_ascii(&buf, out);
+    }
+}
+
+/// Decodes base58 in the block-based format of [base58::from_base58]. Error positions are byte offsets into the
+/// whole input, across calls to `update`.
+#[derive(Debug, Clone, Default)]
+pub struct Base58Decoder {
+    digits: Vec<u8>,
+    position: usize,
+}
+
+impl Base58Decoder {
+    pub fn new() -> Self {
+        Base58Decoder {
+            digits: Vec::with_capacity(FULL_ENCODED_BLOCK_SIZE),
+            position: 0,
+        }
+    }
+
+    /// Decode `s`, appending the bytes for each block that is completed to `out`
+    pub fn update(&mut self, s: &str, out: &mut Vec<u8>) -> Result<(), Base58Error> {
+        for (i, c) in s.bytes().enumerate() {
+            let digit = base58::digit_value(c, i, s, &BASE58_BITCOIN).map_err(|e| match e {
+                Base58Error::InvalidCharacter { index, character } => Base58Error::InvalidCharacter {
+                    index: self.position + index,
+                    character,
+                },
+                e => e,
+            })?;
+            self.digits.push(digit);
+            if self.digits.len() == FULL_ENCODED_BLOCK_SIZE {
+                let block_start = self.position + i + 1 - FULL_ENCODED_BLOCK_SIZE;
+                base58::decode_block(&self.digits, block_start, out)?;
+                self.digits.clear();
+            }
+        }
+        self.position += s.len();
+        Ok(())
+    }
+
+    /// Append the bytes for the final, partial block to `out`
+    pub fn finish(self, out: &mut Vec<u8>) -> Result<(), Base58Error> {
+        base58::decode_block(&self.digits, self.position - self.digits.len(), out)
+    }
+}
+
+/// Encodes base64 with the given configuration
+#[derive(Debug, Clone)]
+pub struct Base64Encoder {
+    config: base64::Config,
+    group: Vec<u8>,
+}
+
+impl Base64Encoder {
+    pub fn new(config: base64::Config) -> Self {
+        Base64Encoder {
+            config,
+            group: Vec::with_capacity(3),
+        }
+    }
+
+    /// Encode `bytes`, appending the characters for every complete 3-byte group to `out`
+    pub fn update(&mut self, mut bytes: &[u8], out: &mut String) {
+        if !self.group.is_empty() {
+            let n = (3 - self.group.len()).min(bytes.len());
+            self.group.extend_from_slice(&bytes[..n]);
+            bytes = &bytes[n..];
+            if self.group.len() < 3 {
+                return;
+            }
+            base64::encode_config_buf(&self.group, self.config, out);
+            self.group.clear();
+        }
+        let whole = bytes.len() / 3 * 3;
+        base64::encode_config_buf(&bytes[..whole], self.config, out);
+        self.group.extend_from_slice(&bytes[whole..]);
+    }
+
+    /// Append the characters for the final, partial group, and any padding, to `out`
+    pub fn finish(self, out: &mut String) {
+        base64::encode_config_buf(&self.group, self.config, out);
+    }
+}
+
+/// Decodes base64 with the given configuration. Error offsets are positions in the whole input, across calls to
+/// `update`.
+#[derive(Debug, Clone)]
+pub struct Base64Decoder {
+    config: base64::Config,
+    group: Vec<u8>,
+    position: usize,
+    padded: bool,
+}
+
+impl Base64Decoder {
+    pub fn new(config: base64::Config) -> Self {
+        Base64Decoder {
+            config,
+            group: Vec::with_capacity(4),
+            position: 0,
+            padded: false,
+        }
+    }
+
+    /// Decode `s`, appending the bytes for every complete 4-character group to `out`
+    pub fn update(&mut self, s: &str, out: &mut Vec<u8>) -> Result<(), base64::DecodeError> {
+        for (i, c) in s.bytes().enumerate() {
+            // Padding can only appear at the very end
+            if self.padded {
+                return Err(base64::DecodeError::InvalidByte(self.position + i, c));
+            }
+            self.group.push(c);
+            if self.group.len() == 4 {
+                self.padded = c == b'=';
+                let group_start = self.position + i - 3;
+                self.decode_group(group_start, out)?;
+            }
+        }
+        self.position += s.len();
+        Ok(())
+    }
+
+    /// Append the bytes for the final, partial group to `out`
+    pub fn finish(mut self, out: &mut Vec<u8>) -> Result<(), base64::DecodeError> {
+        let group_start = self.position - self.group.len();
+        self.decode_group(group_start, out)
+    }
+
+    fn decode_group(&mut self, group_start: usize, out: &mut Vec<u8>) -> Result<(), base64::DecodeError> {
+        let mut buf = [0u8; 3];
+        let n = base64::decode_config_slice(&self.group, self.config, &mut buf).map_err(|e| match e {
+            base64::DecodeError::InvalidByte(offset, byte) => {
+                base64::DecodeError::InvalidByte(group_start + offset, byte)
+            },
+            base64::DecodeError::InvalidLastSymbol(offset, byte) => {
+                base64::DecodeError::InvalidLastSymbol(group_start + offset, byte)
+            },
+            e => e,
+        })?;
+        out.extend_from_slice(&buf[..n]);
+        self.group.clear();
+        Ok(())
+    }
+}
+
+/// Append ASCII encoder output to a string
+fn push_ascii(buf: &[u8], out: &mut String) {
+    out.push_str(std::str::from_utf8(buf).expect("encoder output is ASCII"));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::encoding::base58::{from_base58, to_base58};
+
+    fn data() -> Vec<u8> {
+        (0..1000u32).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn base58_in_pieces() {
+        let data = data();
+        let expected = to_base58(&data);
+        for &size in &[1, 3, 8, 13, 1000] {
+            let mut encoder = Base58Encoder::new();
+            let mut s = String::new();
+            for chunk in data.chunks(size) {
+                encoder.update(chunk, &mut s);
+            }
+            encoder.finish(&mut s);
+            assert_eq!(s, expected);
+
+            let mut decoder = Base58Decoder::new();
+            let mut bytes = Vec::new();
+            for chunk in expected.as_bytes().chunks(size) {
+                decoder.update(std::str::from_utf8(chunk).unwrap(), &mut bytes).unwrap();
+            }
+            decoder.finish(&mut bytes).unwrap();
+            assert_eq!(bytes, data);
+        }
+        assert_eq!(from_base58(&expected).unwrap(), data);
+    }
+
+    #[test]
+    fn base58_errors() {
+        let mut decoder = Base58Decoder::new();
+        let mut bytes = Vec::new();
+        decoder.update("1111111111", &mut bytes).unwrap();
+        assert_eq!(
+            decoder.update("11110", &mut bytes),
+            Err(Base58Error::InvalidCharacter {
+                index: 14,
+                character: '0'
+            })
+        );
+        let mut decoder = Base58Decoder::new();
+        decoder.update("111", &mut bytes).unwrap();
+        decoder.update("1", &mut bytes).unwrap();
+        assert_eq!(
+            decoder.finish(&mut bytes),
+            Err(Base58Error::InvalidBlockSize { index: 0, size: 4 })
+        );
+        let mut decoder = Base58Decoder::new();
+        decoder.update("111111111", &mut bytes).unwrap();
+        decoder.update("11zz", &mut bytes).unwrap();
+        assert_eq!(decoder.finish(&mut bytes), Err(Base58Error::Overflow { index: 11 }));
+    }
+
+    #[test]
+    fn base64_in_pieces() {
+        let data = data();
+        for &config in &[base64::STANDARD, base64::URL_SAFE_NO_PAD] {
+            let expected = base64::encode_config(&data, config);
+            for &size in &[1, 2, 4, 7, 1000] {
+                let mut encoder = Base64Encoder::new(config);
+                let mut s = String::new();
+                for chunk in data.chunks(size) {
+                    encoder.update(chunk, &mut s);
+                }
+                encoder.finish(&mut s);
+                assert_eq!(s, expected);
+
+                let mut decoder = Base64Decoder::new(config);
+                let mut bytes = Vec::new();
+                for chunk in expected.as_bytes().chunks(size) {
+                    decoder.update(std::str::from_utf8(chunk).unwrap(), &mut bytes).unwrap();
+                }
+                decoder.finish(&mut bytes).unwrap();
+                assert_eq!(bytes, data);
+            }
+        }
+    }
+
+    #[test]
+    fn base64_errors() {
+        let mut bytes = Vec::new();
+        let mut decoder = Base64Decoder::new(base64::STANDARD);
+        decoder.update("QUJD", &mut bytes).unwrap();
+        assert_eq!(
+            decoder.update("QU!D", &mut bytes),
+            Err(base64::DecodeError::InvalidByte(6, b'!'))
+        );
+        let mut decoder = Base64Decoder::new(base64::STANDARD);
+        decoder.update("QQ==", &mut bytes).unwrap();
+        assert_eq!(
+            decoder.update("QQ==", &mut bytes),
+            Err(base64::DecodeError::InvalidByte(4, b'Q'))
+        );
+        let mut decoder = Base64Decoder::new(base64::STANDARD);
+        decoder.update("QUJDR", &mut bytes).unwrap();
+        assert!(decoder.finish(&mut bytes).is_err());
+    }
+}
@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
 use chrono::{DateTime, NaiveDateTime, Utc};
 use newtype_ops::newtype_ops;
 use serde::{Deserialize, Serialize};
@@ -31,10 +33,18 @@ pub struct EpochTime(u64);
 
 impl EpochTime {
     /// return UTC current as EpochTime
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn now() -> EpochTime {
         EpochTime(Utc::now().timestamp() as u64)
     }
 
+    /// return UTC current as EpochTime. `std::time` is not available on `wasm32-unknown-unknown`, so the browser clock
+    /// is used instead.
+    #[cfg(all(target_arch = "wasm32", feature = "js"))]
+    pub fn now() -> EpochTime {
+        EpochTime((js_sys::Date::now() / 1000.0) as u64)
+    }
+
     /// Return the EpochTime as a u64
     pub fn as_u64(self) -> u64 {
         self.0
@@ -59,9 +69,10 @@ impl EpochTime {
     }
 }
 
+#[cfg(any(not(target_arch = "wasm32"), feature = "js"))]
 impl Default for EpochTime {
     fn default() -> Self {
-        EpochTime(Utc::now().timestamp() as u64)
+        EpochTime::now()
     }
 }
 
@@ -106,11 +117,23 @@ impl From<EpochTime> for DateTime<Utc> {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl Arbitrary for EpochTime {
+    fn arbitrary(u: &mut Unstructured<'_>) -> arbitrary::Result<Self> {
+        Ok(EpochTime(u64::arbitrary(u)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <u64 as Arbitrary>::size_hint(depth)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
     #[test]
+    #[allow(clippy::op_ref)]
     fn add_epoch_time() {
         assert_eq!(EpochTime::from(1_000) + EpochTime::from(8_000), EpochTime::from(9_000));
         assert_eq!(&EpochTime::from(15) + &EpochTime::from(5), EpochTime::from(20));
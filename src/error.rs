@@ -0,0 +1,161 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+#[cfg(feature = "byte_cursor")]
+use crate::byte_cursor::ByteCursorError;
+#[cfg(feature = "chunking")]
+use crate::chunking::ChunkError;
+#[cfg(feature = "ciphers")]
+use crate::ciphers::cipher::CipherError;
+#[cfg(feature = "encoding")]
+use crate::encoding::{
+    AlphabetError,
+    Base32Error,
+    Base58Error,
+    Bech32Error,
+    DecodeAnyError,
+    EmojiError,
+    MultibaseError,
+    PercentError,
+};
+#[cfg(feature = "message_format")]
+use crate::message_format::{EnvelopeError, MessageFormatError};
+#[cfg(feature = "percentage")]
+use crate::percentage::PercentageError;
+#[cfg(feature = "string")]
+use crate::string::StringError;
+#[cfg(all(feature = "thread_join", not(target_arch = "wasm32")))]
+use crate::thread_join::ThreadError;
+use crate::{
+    checksum::{DammSumError, LuhnError},
+    hex::HexError,
+    serde::duration::DurationError,
+    ByteArrayError,
+};
+use thiserror::Error;
+
+/// An umbrella error for applications that call into several of this crate's modules and just want to propagate
+/// failures with `?`, without writing a conversion for each module's error type.
+#[derive(Debug, Error)]
+pub enum TariUtilError {
+    #[error("{0}")]
+    HexError(#[from] HexError),
+    #[error("{0}")]
+    ByteArrayError(#[from] ByteArrayError),
+    #[error("{0}")]
+    DammSumError(#[from] DammSumError),
+    #[error("{0}")]
+    LuhnError(#[from] LuhnError),
+    #[error("{0}")]
+    DurationError(#[from] DurationError),
+    #[cfg(feature = "byte_cursor")]
+    #[error("{0}")]
+    ByteCursorError(#[from] ByteCursorError),
+    #[cfg(feature = "chunking")]
+    #[error("{0}")]
+    ChunkError(#[from] ChunkError),
+    #[cfg(feature = "encoding")]
+    #[error("{0}")]
+    AlphabetError(#[from] AlphabetError),
+    #[cfg(feature = "encoding")]
+    #[error("{0}")]
+    Base58Error(#[from] Base58Error),
+    #[cfg(feature = "encoding")]
+    #[error("{0}")]
+    Bech32Error(#[from] Bech32Error),
+    #[cfg(feature = "encoding")]
+    #[error("{0}")]
+    Base32Error(#[from] Base32Error),
+    #[cfg(feature = "encoding")]
+    #[error("{0}")]
+    MultibaseError(#[from] MultibaseError),
+    #[cfg(feature = "encoding")]
+    #[error("{0}")]
+    DecodeAnyError(#[from] DecodeAnyError),
+    #[cfg(feature = "encoding")]
+    #[error("{0}")]
+    EmojiError(#[from] EmojiError),
+    #[cfg(feature = "encoding")]
+    #[error("{0}")]
+    PercentError(#[from] PercentError),
+    #[cfg(feature = "message_format")]
+    #[error("{0}")]
+    MessageFormatError(#[from] MessageFormatError),
+    #[cfg(feature = "message_format")]
+    #[error("{0}")]
+    EnvelopeError(#[from] EnvelopeError),
+    #[cfg(feature = "ciphers")]
+    #[error("{0}")]
+    CipherError(#[from] CipherError),
+    #[cfg(feature = "percentage")]
+    #[error("{0}")]
+    PercentageError(#[from] PercentageError),
+    #[cfg(feature = "string")]
+    #[error("{0}")]
+    StringError(#[from] StringError),
+    #[cfg(all(feature = "thread_join", not(target_arch = "wasm32")))]
+    #[error("{0}")]
+    ThreadError(#[from] ThreadError),
+}
+
+#[cfg(all(test, feature = "percentage"))]
+mod test {
+    use super::*;
+    use crate::{hex::Hex, percentage::Percentage, ByteArray};
+
+    fn parse(key: &str, fee: &str) -> Result<u64, TariUtilError> {
+        let key = <[u8; 32]>::from_hex(key)?;
+        let _ = <[u8; 32]>::from_bytes(&key)?;
+        let fee = fee.parse::<Percentage>()?;
+        Ok(fee.checked_mul(100).unwrap_or_default())
+    }
+
+    #[test]
+    fn question_mark_conversions() {
+        let key = "00".repeat(32);
+        assert_eq!(parse(&key, "10%").unwrap(), 10);
+        match parse("zz", "10%") {
+            Err(TariUtilError::HexError(_)) => {},
+            r => panic!("Unexpected result {:?}", r),
+        }
+        match parse(&key, "ten") {
+            Err(e @ TariUtilError::PercentageError(_)) => {
+                assert_eq!(e.to_string(), PercentageError::InvalidFormat.to_string())
+            },
+            r => panic!("Unexpected result {:?}", r),
+        }
+    }
+
+    #[cfg(feature = "message_format")]
+    #[test]
+    fn envelope_error() {
+        let e = TariUtilError::from(EnvelopeError::Truncated);
+        assert_eq!(e.to_string(), "The envelope is truncated");
+    }
+
+    #[cfg(all(feature = "thread_join", not(target_arch = "wasm32")))]
+    #[test]
+    fn thread_error() {
+        let e: TariUtilError = ThreadError::TimeoutReached.into();
+        assert!(matches!(e, TariUtilError::ThreadError(ThreadError::TimeoutReached)));
+    }
+}
@@ -20,6 +20,9 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::percentage::Percentage;
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
 use std::ops::Add;
 
 #[derive(Clone, Debug)]
@@ -67,6 +70,22 @@ impl<T: Clone + PartialEq + Default> FixedSet<T> {
         self.items.iter().all(Option::is_some)
     }
 
+    /// Returns the number of items that have been set
+    pub fn count_filled(&self) -> usize {
+        self.items.iter().filter(|v| v.is_some()).count()
+    }
+
+    /// Returns true if at least `n` items have been set, e.g. to check whether a 2-of-3 threshold has been reached
+    pub fn has_at_least(&self, n: usize) -> bool {
+        self.count_filled() >= n
+    }
+
+    /// Returns true if the items that have been set make up at least `fraction` of the set size. An empty set is
+    /// always a quorum.
+    pub fn is_quorum(&self, fraction: Percentage) -> bool {
+        fraction.is_met_by(self.count_filled() as u64, self.size() as u64)
+    }
+
     /// Return the index of the given item in the set by performing a linear search through the set
     pub fn search(&self, val: &T) -> Option<usize> {
         let key = self
@@ -106,11 +125,25 @@ impl<T: Clone + PartialEq + Default> FixedSet<T> {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<T: Arbitrary> Arbitrary for FixedSet<T> {
+    fn arbitrary(u: &mut Unstructured<'_>) -> arbitrary::Result<Self> {
+        Ok(FixedSet {
+            items: Vec::arbitrary(u)?,
+        })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <Vec<Option<T>> as Arbitrary>::size_hint(depth)
+    }
+}
+
 //-------------------------------------------         Tests              ---------------------------------------------//
 
 #[cfg(test)]
 mod test {
     use super::FixedSet;
+    use crate::percentage::Percentage;
 
     #[derive(Eq, PartialEq, Clone, Debug, Default)]
     struct Foo {
@@ -124,6 +157,8 @@ mod test {
         assert_eq!(s.set_item(1, 1), false, "Should not be able to set item");
         assert_eq!(s.get_item(0), None, "Should not return a value");
         assert_eq!(s.sum(), Some(0));
+        assert_eq!(s.count_filled(), 0);
+        assert!(s.is_quorum(Percentage::ONE_HUNDRED));
     }
 
     fn data(s: &str) -> Foo {
@@ -147,6 +182,23 @@ mod test {
         }
     }
 
+    #[test]
+    fn quorum() {
+        let mut s = FixedSet::<Foo>::new(3);
+        assert!(!s.has_at_least(1));
+        assert!(s.is_quorum(Percentage::ZERO));
+        s.set_item(0, data("vimes"));
+        s.set_item(2, data("carrot"));
+        assert_eq!(s.count_filled(), 2);
+        assert!(s.has_at_least(2));
+        assert!(!s.has_at_least(3));
+        assert!(s.is_quorum(Percentage::from_percent(66).unwrap()));
+        assert!(!s.is_quorum(Percentage::from_percent(67).unwrap()));
+        assert!(!s.is_quorum(Percentage::ONE_HUNDRED));
+        s.set_item(1, data("rincewind"));
+        assert!(s.is_quorum(Percentage::ONE_HUNDRED));
+    }
+
     #[test]
     fn small_set() {
         let mut s = FixedSet::<Foo>::new(3);
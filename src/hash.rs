@@ -20,7 +20,64 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+#[cfg(feature = "digest")]
+use digest::{generic_array::GenericArray, Digest};
+#[cfg(feature = "digest")]
+use std::io;
+
+/// The output of hashing with the digest `D`
+#[cfg(feature = "digest")]
+pub type Output<D> = GenericArray<u8, <D as Digest>::OutputSize>;
+
 /// This trait is used to describe how an object should be hashed
 pub trait Hashable {
     fn hash(&self) -> Vec<u8>;
 }
+
+/// The size of the buffer used by [hash_reader]
+#[cfg(feature = "digest")]
+pub const DEFAULT_HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Hash everything that can be read from `reader` with the digest `D`, reading it in chunks so that large files or
+/// streams do not need to be loaded into memory first.
+#[cfg(feature = "digest")]
+pub fn hash_reader<D: Digest, R: io::Read>(reader: &mut R) -> Result<Output<D>, io::Error> {
+    hash_reader_with_buffer_size::<D, R>(reader, DEFAULT_HASH_BUFFER_SIZE)
+}
+
+/// As for [hash_reader], but reading in chunks of `buffer_size` bytes
+#[cfg(feature = "digest")]
+pub fn hash_reader_with_buffer_size<D: Digest, R: io::Read>(
+    reader: &mut R,
+    buffer_size: usize,
+) -> Result<Output<D>, io::Error>
+{
+    let mut hasher = D::new();
+    let mut buf = vec![0u8; buffer_size.max(1)];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(hasher.result()),
+            Ok(n) => hasher.input(&buf[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "digest"))]
+mod test {
+    use super::*;
+    use sha2::Sha256;
+
+    #[test]
+    fn hash_reader_matches_digest() {
+        let data = (0..200_000u32).map(|i| i as u8).collect::<Vec<u8>>();
+        let expected = Sha256::digest(&data);
+        assert_eq!(hash_reader::<Sha256, _>(&mut data.as_slice()).unwrap(), expected);
+        assert_eq!(
+            hash_reader_with_buffer_size::<Sha256, _>(&mut data.as_slice(), 7).unwrap(),
+            expected
+        );
+        assert_eq!(hash_reader::<Sha256, _>(&mut io::empty()).unwrap(), Sha256::digest(b""));
+    }
+}
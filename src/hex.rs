@@ -1,9 +1,18 @@
-use serde::Serializer;
+use crate::{checksum::crc32, ByteArray};
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
+#[cfg(feature = "clear_on_drop")]
+use clear_on_drop::ClearOnDrop;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use serde::{de, Deserialize, Deserializer, Serializer};
 use std::{
-    fmt::{LowerHex, Write},
-    num::ParseIntError,
+    fmt::{self, LowerHex, Write},
+    io,
 };
 use thiserror::Error;
+#[cfg(feature = "tiny-keccak")]
+use tiny_keccak::{Hasher, Keccak};
 
 /// Any object implementing this trait has the ability to represent itself as a hexadecimal string and convert from it.
 pub trait Hex {
@@ -14,63 +23,569 @@ pub trait Hex {
 
     /// Return the hexadecimal string representation of the type
     fn to_hex(&self) -> String;
+
+    /// Return the hexadecimal string representation of the type, using uppercase digits
+    fn to_hex_upper(&self) -> String {
+        self.to_hex().to_ascii_uppercase()
+    }
 }
 
+/// Hex conversions for integers with an explicit byte order. The [Hex] implementations for integers are big-endian,
+/// the same as `to_hex_be`, so that the hex reads like the number.
+pub trait HexEndian: Sized {
+    /// Return the fixed-width hex representation of the big-endian bytes of the integer
+    fn to_hex_be(&self) -> String;
+    /// Return the fixed-width hex representation of the little-endian bytes of the integer
+    fn to_hex_le(&self) -> String;
+    /// Parse the fixed-width hex representation of the big-endian bytes of an integer
+    fn from_hex_be(hex: &str) -> Result<Self, HexError>;
+    /// Parse the fixed-width hex representation of the little-endian bytes of an integer
+    fn from_hex_le(hex: &str) -> Result<Self, HexError>;
+}
+
+macro_rules! impl_integer_hex {
+    ($($t:ty),*) => {$(
+        impl HexEndian for $t {
+            fn to_hex_be(&self) -> String {
+                to_hex(&self.to_be_bytes())
+            }
+
+            fn to_hex_le(&self) -> String {
+                to_hex(&self.to_le_bytes())
+            }
+
+            fn from_hex_be(hex: &str) -> Result<Self, HexError> {
+                Ok(Self::from_be_bytes(from_hex_fixed(hex)?))
+            }
+
+            fn from_hex_le(hex: &str) -> Result<Self, HexError> {
+                Ok(Self::from_le_bytes(from_hex_fixed(hex)?))
+            }
+        }
+
+        impl Hex for $t {
+            fn from_hex(hex: &str) -> Result<Self, HexError> {
+                Self::from_hex_be(hex)
+            }
+
+            fn to_hex(&self) -> String {
+                self.to_hex_be()
+            }
+        }
+    )*};
+}
+
+impl_integer_hex!(u16, u32, u64, u128);
+
 #[derive(Debug, Error)]
 pub enum HexError {
-    #[error("Only hexadecimal characters (0-9,a-f) are permitted")]
-    InvalidCharacter(#[from] ParseIntError),
-    #[error("Hex string lengths must be a multiple of 2")]
-    LengthError,
+    #[error("Only hexadecimal characters (0-9,a-f) are permitted, but found '{character}' at position {index}")]
+    InvalidCharacter { index: usize, character: char },
+    #[error("Hex string lengths must be a multiple of 2 and fit the target type, but the string has {length} digits")]
+    LengthError { length: usize },
     #[error("Invalid hex representation for the target type")]
     HexConversionError,
+    #[error("The checksum does not match the data. Check the value for typos")]
+    ChecksumMismatch,
+    #[error("The output buffer is too small: {required} bytes are needed but only {actual} are available")]
+    BufferTooSmall { required: usize, actual: usize },
+    #[error("The input is too large to hex-encode: {actual} bytes exceeds the limit of {max}")]
+    InputTooLarge { max: usize, actual: usize },
+    #[error("An I/O error occurred while streaming hex: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Item {index} of the batch is invalid: {source}")]
+    InvalidItem {
+        index: usize,
+        #[source]
+        source: Box<HexError>,
+    },
 }
 
 /// Encode the provided bytes into a hex string
-pub fn to_hex<T>(bytes: &[T]) -> String
+pub fn to_hex(bytes: &[u8]) -> String {
+    encode_with_digits(bytes, HEX_DIGITS)
+}
+
+/// Encode each of the provided values into hex with `{:02x}` and concatenate them, for slices of integers other than
+/// bytes. Prefer [to_hex] for bytes, which looks each nibble up in a table instead of formatting every value.
+pub fn to_hex_generic<T>(values: &[T]) -> String
 where T: LowerHex {
-    let mut s = String::new();
+    let mut s = String::with_capacity(values.len() * 2);
+    for value in values {
+        write!(&mut s, "{:02x}", value).expect("Unable to write");
+    }
+    s
+}
+
+/// The lowercase hex digits, indexed by nibble value
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+/// The uppercase hex digits, indexed by nibble value
+const HEX_DIGITS_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Encode the provided bytes into a hex string, failing with [HexError::InputTooLarge] if there are more than
+/// `max_bytes` of them. Pass `None` for no limit.
+pub fn try_to_hex(bytes: &[u8], max_bytes: Option<usize>) -> Result<String, HexError> {
+    match max_bytes {
+        Some(max) if bytes.len() > max => Err(HexError::InputTooLarge {
+            max,
+            actual: bytes.len(),
+        }),
+        _ => Ok(to_hex(bytes)),
+    }
+}
+
+/// Encode the provided bytes into a hex string using uppercase digits
+pub fn to_hex_upper(bytes: &[u8]) -> String {
+    encode_with_digits(bytes, HEX_DIGITS_UPPER)
+}
+
+/// Encode the bytes into a hex string by looking up each nibble in `digits`, writing straight into a buffer of the
+/// final size
+fn encode_with_digits(bytes: &[u8], digits: &[u8; 16]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
     for byte in bytes {
-        write!(&mut s, "{:02x}", byte).expect("Unable to write");
+        s.push(char::from(digits[usize::from(byte >> 4)]));
+        s.push(char::from(digits[usize::from(byte & 0x0f)]));
     }
     s
 }
 
-/// Encode the provided vector of bytes into a hex string
-pub fn to_hex_multiple(bytearray: &[Vec<u8>]) -> Vec<String> {
-    let mut result = Vec::new();
-    for bytes in bytearray {
-        result.push(to_hex(bytes))
+/// Encode the provided bytes as hex into the caller-supplied buffer `out`, without allocating. `out` must be at least
+/// twice as long as `bytes`; the returned string borrows the written part of `out`.
+pub fn to_hex_into<'a>(bytes: &[u8], out: &'a mut [u8]) -> Result<&'a str, HexError> {
+    let required = bytes.len() * 2;
+    if out.len() < required {
+        return Err(HexError::BufferTooSmall {
+            required,
+            actual: out.len(),
+        });
+    }
+    for (byte, pair) in bytes.iter().zip(out.chunks_mut(2)) {
+        pair[0] = HEX_DIGITS[usize::from(byte >> 4)];
+        pair[1] = HEX_DIGITS[usize::from(byte & 0x0f)];
     }
-    result
+    Ok(std::str::from_utf8(&out[..required]).expect("Hex digits are always valid UTF-8"))
 }
 
-/// Decode a hex string into bytes.
-pub fn from_hex(hex_str: &str) -> Result<Vec<u8>, HexError> {
-    let hex_trim = hex_str.trim();
-    if !hex_str.is_ascii() {
-        return Err(HexError::HexConversionError);
+/// Encode each of the provided byte arrays into a hex string
+pub fn to_hex_multiple<T: ByteArray>(bytearray: &[T]) -> Vec<String> {
+    bytearray.iter().map(|bytes| to_hex(bytes.as_bytes())).collect()
+}
+
+/// Encode each of the byte slices yielded by `bytearrays` into a hex string. Unlike [to_hex_multiple], this accepts
+/// any iterator, and anything that can be viewed as bytes.
+pub fn to_hex_iter<I>(bytearrays: I) -> Vec<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    bytearrays.into_iter().map(|bytes| to_hex(bytes.as_ref())).collect()
+}
+
+/// Encode each of the provided byte arrays into a hex string, failing with [HexError::InputTooLarge] on the first one
+/// that is longer than `max_bytes`, as for [try_to_hex]
+pub fn try_to_hex_multiple<I>(bytearrays: I, max_bytes: Option<usize>) -> Result<Vec<String>, HexError>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    bytearrays
+        .into_iter()
+        .map(|bytes| try_to_hex(bytes.as_ref(), max_bytes))
+        .collect()
+}
+
+/// Encode each of the provided byte arrays into hex and join them with `separator`, e.g. for a comma-separated list
+pub fn to_hex_joined<I>(bytearrays: I, separator: &str) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut s = String::new();
+    for (i, bytes) in bytearrays.into_iter().enumerate() {
+        if i > 0 {
+            s.push_str(separator);
+        }
+        s.push_str(&to_hex(bytes.as_ref()));
     }
-    let hex_trim = if (hex_trim.len() >= 2) && (&hex_trim[..2] == "0x") {
-        &hex_trim[2..]
+    s
+}
+
+/// Encode each of the provided byte arrays into a hex string, spreading the work over the rayon thread pool.
+#[cfg(feature = "rayon")]
+pub fn to_hex_multiple_par<T: ByteArray + Sync>(bytearray: &[T]) -> Vec<String> {
+    bytearray.par_iter().map(|bytes| to_hex(bytes.as_bytes())).collect()
+}
+
+/// Decode each of the provided hex strings into bytes. If any of the strings are invalid, the first failure is returned
+/// as [HexError::InvalidItem], which records the position of the offending string in `hex_strs`.
+pub fn from_hex_multiple<S: AsRef<str>>(hex_strs: &[S]) -> Result<Vec<Vec<u8>>, HexError> {
+    hex_strs
+        .iter()
+        .enumerate()
+        .map(|(index, s)| from_hex_item(index, s.as_ref()))
+        .collect()
+}
+
+/// Decode each of the provided hex strings into bytes, spreading the work over the rayon thread pool. If several
+/// strings are invalid, which of the failures is returned is unspecified.
+#[cfg(feature = "rayon")]
+pub fn from_hex_multiple_par<S: AsRef<str> + Sync>(hex_strs: &[S]) -> Result<Vec<Vec<u8>>, HexError> {
+    hex_strs
+        .par_iter()
+        .enumerate()
+        .map(|(index, s)| from_hex_item(index, s.as_ref()))
+        .collect()
+}
+
+fn from_hex_item(index: usize, hex_str: &str) -> Result<Vec<u8>, HexError> {
+    from_hex(hex_str).map_err(|e| HexError::InvalidItem {
+        index,
+        source: Box::new(e),
+    })
+}
+
+/// Marks a byte that is not a hex digit in [HEX_VALUES]
+const INVALID_DIGIT: u8 = 0xff;
+
+/// The value of each ASCII hex digit (in either case), indexed by character. Other characters map to `INVALID_DIGIT`.
+#[rustfmt::skip]
+const HEX_VALUES: [u8; 256] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+/// Return the value of a single ASCII hex digit. `index` is the position of `c` in the input, for error reporting.
+fn hex_digit_value(c: u8, index: usize) -> Result<u8, HexError> {
+    match HEX_VALUES[usize::from(c)] {
+        INVALID_DIGIT => Err(HexError::InvalidCharacter {
+            index,
+            // A lone non-ASCII byte is not a complete character
+            character: if c.is_ascii() {
+                char::from(c)
+            } else {
+                std::char::REPLACEMENT_CHARACTER
+            },
+        }),
+        v => Ok(v),
+    }
+}
+
+/// Check that `hex_str` is ASCII, then trim surrounding whitespace and any `0x` prefix. Returns the remaining digits
+/// and their offset in `hex_str`.
+pub(crate) fn trim_hex(hex_str: &str) -> Result<(&str, usize), HexError> {
+    if let Some((index, character)) = hex_str.char_indices().find(|(_, c)| !c.is_ascii()) {
+        return Err(HexError::InvalidCharacter { index, character });
+    }
+    let hex_trim = hex_str.trim();
+    let offset = hex_str.len() - hex_str.trim_start().len();
+    if (hex_trim.len() >= 2) && (&hex_trim[..2] == "0x") {
+        Ok((&hex_trim[2..], offset + 2))
     } else {
-        hex_trim
-    };
+        Ok((hex_trim, offset))
+    }
+}
+
+/// Decode a hex string into bytes. Error positions are byte offsets into `hex_str`.
+pub fn from_hex(hex_str: &str) -> Result<Vec<u8>, HexError> {
+    let (hex_trim, offset) = trim_hex(hex_str)?;
     if hex_trim.len() % 2 == 1 {
-        return Err(HexError::LengthError);
+        return Err(HexError::LengthError { length: hex_trim.len() });
     }
-    let num_bytes = hex_trim.len() / 2;
-    let mut result = vec![0u8; num_bytes];
-    for i in 0..num_bytes {
-        let val = u8::from_str_radix(&hex_trim[2 * i..2 * (i + 1)], 16);
-        result[i] = match val {
-            Ok(v) => v,
-            Err(e) => return Err(HexError::InvalidCharacter(e)),
+    hex_trim
+        .as_bytes()
+        .chunks(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let index = offset + 2 * i;
+            Ok(hex_digit_value(pair[0], index)? << 4 | hex_digit_value(pair[1], index + 1)?)
+        })
+        .collect()
+}
+
+/// Decode a hex string lazily, one byte at a time, without allocating. The input is accepted and errors are reported
+/// exactly as for [from_hex]; the iterator ends after the first error.
+pub fn decode_iter(hex_str: &str) -> DecodeIter<'_> {
+    let (digits, offset, error) = match trim_hex(hex_str) {
+        Ok((digits, _)) if digits.len() % 2 == 1 => (&[][..], 0, Some(HexError::LengthError { length: digits.len() })),
+        Ok((digits, offset)) => (digits.as_bytes(), offset, None),
+        Err(e) => (&[][..], 0, Some(e)),
+    };
+    DecodeIter { digits, offset, error }
+}
+
+/// The iterator returned by [decode_iter]
+#[derive(Debug)]
+pub struct DecodeIter<'a> {
+    digits: &'a [u8],
+    offset: usize,
+    error: Option<HexError>,
+}
+
+impl Iterator for DecodeIter<'_> {
+    type Item = Result<u8, HexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
         }
+        if self.digits.is_empty() {
+            return None;
+        }
+        let (pair, rest) = self.digits.split_at(2);
+        let index = self.offset;
+        self.digits = rest;
+        self.offset += 2;
+        let value = hex_digit_value(pair[0], index).and_then(|hi| Ok(hi << 4 | hex_digit_value(pair[1], index + 1)?));
+        if value.is_err() {
+            self.digits = &[];
+        }
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.digits.len() / 2 + if self.error.is_some() { 1 } else { 0 };
+        (0, Some(len))
+    }
+}
+
+impl std::iter::FusedIterator for DecodeIter<'_> {}
+
+/// Decode a single hex digit without branching on its value. Returns the nibble and a mask that is `0xff` if `c` is a
+/// hex digit and `0` otherwise.
+#[cfg(feature = "clear_on_drop")]
+fn ct_hex_digit_value(c: u8) -> (u8, u8) {
+    let c = i16::from(c);
+    // Each mask is all ones if `c` is in the range and zero otherwise, from the sign bit of the bounds checks
+    let digit_mask = ((0x2f - c) & (c - 0x3a)) >> 8;
+    let lower = c | 0x20;
+    let alpha_mask = ((0x60 - lower) & (lower - 0x67)) >> 8;
+    let value = ((c - 0x30) & digit_mask) | ((lower - 0x57) & alpha_mask);
+    (value as u8, (digit_mask | alpha_mask) as u8)
+}
+
+/// Decode a hex-encoded secret, such as a private key or seed read from a config file. The digits are decoded without
+/// data-dependent branches or lookups, and the bytes are written straight into a buffer that is zeroed when dropped.
+/// Surrounding whitespace and a `0x` prefix are accepted, as for [from_hex]. To avoid revealing where an invalid digit
+/// is, any invalid input is reported as [HexError::HexConversionError]. The caller remains responsible for clearing
+/// `hex_str` itself.
+#[cfg(feature = "clear_on_drop")]
+pub fn from_hex_ct(hex_str: &str) -> Result<ClearOnDrop<Box<[u8]>>, HexError> {
+    // Strip the prefix by hand rather than with `trim_hex`, whose ASCII check reports where the first invalid
+    // character is
+    let mut digits = hex_str.trim().as_bytes();
+    if digits.starts_with(b"0x") {
+        digits = &digits[2..];
+    }
+    if digits.len() % 2 == 1 {
+        return Err(HexError::LengthError { length: digits.len() });
     }
+    // Wrapped before decoding starts, so that the bytes decoded before an invalid digit are cleared too
+    let mut result = ClearOnDrop::new(vec![0u8; digits.len() / 2].into_boxed_slice());
+    let mut valid = 0xffu8;
+    for (pair, byte) in digits.chunks(2).zip(result.iter_mut()) {
+        let (high, high_valid) = ct_hex_digit_value(pair[0]);
+        let (low, low_valid) = ct_hex_digit_value(pair[1]);
+        *byte = high << 4 | low;
+        valid &= high_valid & low_valid;
+    }
+    if valid != 0xff {
+        return Err(HexError::HexConversionError);
+    }
+    Ok(result)
+}
+
+/// Decode a hex string straight into a fixed-size byte array such as `[u8; 32]`, without an intermediate `Vec`. Fails
+/// with [HexError::LengthError] if the string does not encode exactly as many bytes as the array holds. Arrays longer
+/// than 32 bytes don't implement `Default`, so decode those with [from_hex_into].
+///
+/// ```
+/// use tari_utilities::hex::from_hex_fixed;
+/// let hash: [u8; 4] = from_hex_fixed("deadbeef").unwrap();
+/// assert_eq!(hash, [0xde, 0xad, 0xbe, 0xef]);
+/// assert!(from_hex_fixed::<[u8; 4]>("dead").is_err());
+/// ```
+pub fn from_hex_fixed<A>(hex_str: &str) -> Result<A, HexError>
+where A: Default + AsMut<[u8]> {
+    let mut result = A::default();
+    from_hex_into(hex_str, result.as_mut())?;
     Ok(result)
 }
 
+/// Decode a hex string into `out`, which must be exactly as long as the decoded bytes, failing with
+/// [HexError::LengthError] otherwise. This works for buffers of any size, e.g. 64-byte signatures.
+///
+/// ```
+/// use tari_utilities::hex::from_hex_into;
+/// let mut signature = [0u8; 64];
+/// from_hex_into(&"ab".repeat(64), &mut signature).unwrap();
+/// assert!(signature.iter().all(|&b| b == 0xab));
+/// ```
+pub fn from_hex_into(hex_str: &str, out: &mut [u8]) -> Result<(), HexError> {
+    let (hex_trim, offset) = trim_hex(hex_str)?;
+    if hex_trim.len() != out.len() * 2 {
+        return Err(HexError::LengthError { length: hex_trim.len() });
+    }
+    for (i, (pair, byte)) in hex_trim.as_bytes().chunks(2).zip(out.iter_mut()).enumerate() {
+        let index = offset + 2 * i;
+        *byte = hex_digit_value(pair[0], index)? << 4 | hex_digit_value(pair[1], index + 1)?;
+    }
+    Ok(())
+}
+
+/// Characters that [from_hex_lenient] accepts between bytes
+const LENIENT_SEPARATORS: &[u8] = b": _\t\r\n";
+
+/// Decode a hex string into bytes, as for [from_hex], but also accept colons, spaces and underscores between bytes
+/// (e.g. `de:ad:be:ef`), as found in fingerprints copied from other tools. A separator between the two digits of a
+/// byte is still rejected.
+pub fn from_hex_lenient(hex_str: &str) -> Result<Vec<u8>, HexError> {
+    let (hex_trim, offset) = trim_hex(hex_str)?;
+    let mut result = Vec::with_capacity(hex_trim.len() / 2);
+    // The high nibble of a byte whose low nibble has not been read yet
+    let mut pending: Option<u8> = None;
+    let mut digits = 0;
+    for (i, &c) in hex_trim.as_bytes().iter().enumerate() {
+        if pending.is_none() && LENIENT_SEPARATORS.contains(&c) {
+            continue;
+        }
+        let nibble = hex_digit_value(c, offset + i)?;
+        digits += 1;
+        match pending.take() {
+            Some(high) => result.push(high << 4 | nibble),
+            None => pending = Some(nibble),
+        }
+    }
+    if pending.is_some() {
+        return Err(HexError::LengthError { length: digits });
+    }
+    Ok(result)
+}
+
+/// The positions of the hyphens in the 8-4-4-4-12 UUID form
+const UUID_HYPHENS: [usize; 4] = [8, 13, 18, 23];
+
+/// Format 16 bytes in the canonical hyphenated UUID form, e.g. `123e4567-e89b-12d3-a456-426614174000`
+pub fn to_uuid_string(bytes: &[u8; 16]) -> String {
+    let hex = to_hex(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Parse the hyphenated UUID form, in either case. Fails with [HexError::HexConversionError] if the string is not 36
+/// characters long with hyphens in the right places, and with [HexError::InvalidCharacter] for any other non-hex
+/// digit.
+pub fn from_uuid_string(uuid: &str) -> Result<[u8; 16], HexError> {
+    if let Some((index, character)) = uuid.char_indices().find(|(_, c)| !c.is_ascii()) {
+        return Err(HexError::InvalidCharacter { index, character });
+    }
+    let chars = uuid.as_bytes();
+    if chars.len() != 36 || UUID_HYPHENS.iter().any(|&i| chars[i] != b'-') {
+        return Err(HexError::HexConversionError);
+    }
+    let mut nibbles = chars
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !UUID_HYPHENS.contains(i))
+        .map(|(i, &c)| hex_digit_value(c, i));
+    let mut result = [0u8; 16];
+    for byte in result.iter_mut() {
+        // There are exactly 32 digits once the hyphens are removed
+        *byte = nibbles.next().unwrap()? << 4 | nibbles.next().unwrap()?;
+    }
+    Ok(result)
+}
+
+/// The number of bytes of checksum appended by [to_hex_checksummed]
+const CHECKSUM_LENGTH: usize = 4;
+
+/// Encode the provided bytes into a hex string with a CRC-32 checksum of the bytes appended, so that typos made when
+/// copying the value by hand (e.g. into a config file) are detected by [from_hex_checksummed].
+pub fn to_hex_checksummed(bytes: &[u8]) -> String {
+    let mut data = Vec::with_capacity(bytes.len() + CHECKSUM_LENGTH);
+    data.extend_from_slice(bytes);
+    data.extend_from_slice(&crc32(bytes).to_be_bytes());
+    to_hex(&data)
+}
+
+/// Decode a hex string produced by [to_hex_checksummed], returning [HexError::ChecksumMismatch] if the checksum does
+/// not match the data.
+pub fn from_hex_checksummed(hex_str: &str) -> Result<Vec<u8>, HexError> {
+    let mut data = from_hex(hex_str)?;
+    if data.len() < CHECKSUM_LENGTH {
+        return Err(HexError::ChecksumMismatch);
+    }
+    let checksum = data.split_off(data.len() - CHECKSUM_LENGTH);
+    if checksum[..] != crc32(&data).to_be_bytes()[..] {
+        return Err(HexError::ChecksumMismatch);
+    }
+    Ok(data)
+}
+
+/// The Keccak-256 hash of the lowercase hex digits, which decides the case of each letter in a mixed-case checksum
+#[cfg(feature = "tiny-keccak")]
+fn mixed_case_checksum_hash(lower_hex: &str) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(lower_hex.as_bytes());
+    keccak.finalize(&mut hash);
+    hash
+}
+
+/// Encode the provided bytes into hex with an EIP-55 style mixed-case checksum: each letter is uppercased if the
+/// corresponding nibble of the Keccak-256 hash of the lowercase hex is 8 or more. This lets [validate_hex_checksum]
+/// catch most typos in values pasted by hand without adding any characters.
+#[cfg(feature = "tiny-keccak")]
+pub fn to_hex_mixed_case_checksum(bytes: &[u8]) -> String {
+    let lower = to_hex(bytes);
+    let hash = mixed_case_checksum_hash(&lower);
+    lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Decode a hex string produced by [to_hex_mixed_case_checksum] (with or without a `0x` prefix), returning
+/// [HexError::ChecksumMismatch] if the case of any letter does not match the checksum. Unlike some wallets, an all
+/// lowercase or all uppercase string is not accepted as "unchecksummed".
+#[cfg(feature = "tiny-keccak")]
+pub fn validate_hex_checksum(hex_str: &str) -> Result<Vec<u8>, HexError> {
+    let bytes = from_hex(hex_str)?;
+    let (digits, _) = trim_hex(hex_str)?;
+    if digits != to_hex_mixed_case_checksum(&bytes) {
+        return Err(HexError::ChecksumMismatch);
+    }
+    Ok(bytes)
+}
+
 pub fn serialize_to_hex<S, T>(t: &T, ser: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -79,6 +594,231 @@ where
     ser.serialize_str(&t.to_hex())
 }
 
+/// Encode the provided bytes into a hex string with a `0x` prefix, as used by Ethereum-style tooling. [from_hex]
+/// accepts the prefix, so the result round-trips.
+pub fn to_hex_prefixed(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2 + 2);
+    s.push_str("0x");
+    s.push_str(&to_hex(bytes));
+    s
+}
+
+/// As for [serialize_to_hex], but with a `0x` prefix. Use with `#[serde(serialize_with = "...")]`.
+pub fn serialize_to_hex_prefixed<S, T>(t: &T, ser: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Hex,
+{
+    ser.serialize_str(&format!("0x{}", t.to_hex()))
+}
+
+/// Deserialize a value from its hex representation, with or without a `0x` prefix. This is the counterpart of
+/// [serialize_to_hex] and [serialize_to_hex_prefixed]; use with `#[serde(deserialize_with = "...")]`.
+pub fn deserialize_from_hex<'de, D, T>(de: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Hex,
+{
+    let hex = String::deserialize(de)?;
+    T::from_hex(&hex).map_err(de::Error::custom)
+}
+
+/// The chunk size used when streaming data through [encode_to_writer] and [decode_from_reader]
+const STREAM_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Hex-encode everything that can be read from `reader`, writing the hex digits to `writer` a chunk at a time rather
+/// than building the whole string in memory. Returns the number of bytes read.
+pub fn encode_to_writer<R: io::Read, W: io::Write>(reader: &mut R, writer: &mut W) -> Result<u64, HexError> {
+    let mut buf = [0u8; STREAM_CHUNK_SIZE];
+    let mut total = 0u64;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return Ok(total),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        writer.write_all(to_hex(&buf[..n]).as_bytes())?;
+        total += n as u64;
+    }
+}
+
+/// Decode the hex digits read from `reader`, writing the bytes to `writer` a chunk at a time. ASCII whitespace (e.g.
+/// the line breaks in `xxd -p` output) is skipped, and so is a leading `0x`, as for [from_hex]. Returns the number of
+/// bytes written.
+pub fn decode_from_reader<R: io::Read, W: io::Write>(reader: &mut R, writer: &mut W) -> Result<u64, HexError> {
+    let mut buf = [0u8; STREAM_CHUNK_SIZE];
+    let mut out = Vec::with_capacity(STREAM_CHUNK_SIZE / 2);
+    // The high nibble of a byte whose low nibble has not been read yet
+    let mut pending: Option<u8> = None;
+    let mut total = 0u64;
+    let mut position = 0usize;
+    let mut digits = 0usize;
+    // A leading "0x" may be split across reads, so it is recognised by the 'x' that follows a single '0' digit
+    let mut prefix_allowed = true;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for (i, &c) in buf[..n].iter().enumerate().filter(|(_, c)| !c.is_ascii_whitespace()) {
+            if prefix_allowed && c == b'x' && digits == 1 && pending == Some(0) {
+                prefix_allowed = false;
+                pending = None;
+                digits = 0;
+                continue;
+            }
+            let nibble = hex_digit_value(c, position + i)?;
+            digits += 1;
+            match pending.take() {
+                Some(high) => out.push(high << 4 | nibble),
+                None => pending = Some(nibble),
+            }
+        }
+        writer.write_all(&out)?;
+        total += out.len() as u64;
+        out.clear();
+        position += n;
+    }
+    if pending.is_some() {
+        return Err(HexError::LengthError { length: digits });
+    }
+    Ok(total)
+}
+
+/// A `Display` adaptor that writes the wrapped bytes as lowercase hex straight to the formatter, so that log statements
+/// can print byte slices without allocating a string with [to_hex]. `LowerHex` and `UpperHex` are implemented too, and
+/// the alternate flag (`{:#x}`) adds a `0x` prefix.
+#[derive(Clone, Copy, Debug)]
+pub struct HexFmt<'a>(pub &'a [u8]);
+
+impl HexFmt<'_> {
+    fn write_with_digits(&self, f: &mut fmt::Formatter<'_>, digits: &[u8; 16]) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        let mut buf = [0u8; 64];
+        for chunk in self.0.chunks(buf.len() / 2) {
+            for (byte, pair) in chunk.iter().zip(buf.chunks_mut(2)) {
+                pair[0] = digits[usize::from(byte >> 4)];
+                pair[1] = digits[usize::from(byte & 0x0f)];
+            }
+            f.write_str(std::str::from_utf8(&buf[..chunk.len() * 2]).map_err(|_| fmt::Error)?)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for HexFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with_digits(f, HEX_DIGITS)
+    }
+}
+
+impl fmt::LowerHex for HexFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with_digits(f, HEX_DIGITS)
+    }
+}
+
+impl fmt::UpperHex for HexFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with_digits(f, HEX_DIGITS_UPPER)
+    }
+}
+
+/// The number of bytes shown on each line of a [HexDump]
+const DUMP_BYTES_PER_LINE: usize = 16;
+
+/// Produce an `xxd`-style hex dump of the bytes: each line shows the offset, 16 bytes in groups of two and the
+/// printable ASCII characters, which is handy when debugging wire messages.
+///
+/// ```
+/// use tari_utilities::hex::dump;
+/// assert_eq!(
+///     dump(b"Hello, world!\n"),
+///     "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a       Hello, world!.\n"
+/// );
+/// ```
+pub fn dump(bytes: &[u8]) -> String {
+    HexDump(bytes).to_string()
+}
+
+/// A `Display` adaptor that formats the wrapped bytes as a hex dump, as for [dump], without building the whole string
+/// first
+#[derive(Clone, Copy, Debug)]
+pub struct HexDump<'a>(pub &'a [u8]);
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (line, chunk) in self.0.chunks(DUMP_BYTES_PER_LINE).enumerate() {
+            write!(f, "{:08x}:", line * DUMP_BYTES_PER_LINE)?;
+            for i in 0..DUMP_BYTES_PER_LINE {
+                if i % 2 == 0 {
+                    f.write_str(" ")?;
+                }
+                match chunk.get(i) {
+                    Some(b) => write!(f, "{:02x}", b)?,
+                    None => f.write_str("  ")?,
+                }
+            }
+            f.write_str("  ")?;
+            for &b in chunk {
+                let c = if b == b' ' || b.is_ascii_graphic() {
+                    char::from(b)
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A well-formed hexadecimal string, for use as fuzzing or property-testing input
+#[cfg(feature = "arbitrary")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidHex(pub String);
+
+#[cfg(feature = "arbitrary")]
+impl Arbitrary for ValidHex {
+    fn arbitrary(u: &mut Unstructured<'_>) -> arbitrary::Result<Self> {
+        let hex = to_hex(&Vec::<u8>::arbitrary(u)?);
+        // `from_hex` accepts either case, so generate both
+        if bool::arbitrary(u)? {
+            Ok(ValidHex(hex.to_uppercase()))
+        } else {
+            Ok(ValidHex(hex))
+        }
+    }
+}
+
+/// A string that `from_hex` is guaranteed to reject, for use as fuzzing or property-testing input
+#[cfg(feature = "arbitrary")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidHex(pub String);
+
+#[cfg(feature = "arbitrary")]
+impl Arbitrary for InvalidHex {
+    fn arbitrary(u: &mut Unstructured<'_>) -> arbitrary::Result<Self> {
+        // 'x' is left out since a leading "0x" is accepted, and whitespace since it is trimmed
+        const NON_HEX: &[u8] = b"ghijklmnopqrstuvwyzGHIJKLMNOPQRSTUVWYZ!#$%&*+-./:;<=>?@_~";
+        let ValidHex(mut hex) = ValidHex::arbitrary(u)?;
+        if bool::arbitrary(u)? {
+            // An odd number of digits
+            hex.push(*u.choose(b"0123456789abcdef")? as char);
+        } else {
+            let index = u.int_in_range(0..=hex.len())?;
+            hex.insert(index, *u.choose(NON_HEX)? as char);
+        }
+        Ok(InvalidHex(hex))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -103,17 +843,413 @@ mod test {
         assert!(from_hex("🖖🥴").is_err());
     }
 
+    #[test]
+    fn encode_every_byte() {
+        let bytes = (0..=255u8).collect::<Vec<u8>>();
+        let expected = bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        assert_eq!(to_hex(&bytes), expected);
+        assert_eq!(to_hex_generic(&bytes), expected);
+        assert_eq!(to_hex_generic(&[0x1u16, 0xabc]), "01abc");
+    }
+
+    #[test]
+    fn size_limit() {
+        assert_eq!(try_to_hex(&[1, 2, 3], Some(3)).unwrap(), "010203");
+        assert_eq!(try_to_hex(&[0; 1024], None).unwrap().len(), 2048);
+        assert!(match try_to_hex(&[1, 2, 3], Some(2)) {
+            Err(HexError::InputTooLarge { max: 2, actual: 3 }) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn upper_case() {
+        assert_eq!(to_hex_upper(&[10, 11, 0xfe, 0xff]), "0A0BFEFF");
+        assert_eq!(vec![0xabu8, 0xcd].to_hex_upper(), "ABCD");
+        assert_eq!(from_hex(&to_hex_upper(&[0xde, 0xad])).unwrap(), vec![0xde, 0xad]);
+    }
+
+    #[test]
+    fn hex_into_buffer() {
+        let mut buf = [0u8; 10];
+        assert_eq!(to_hex_into(&[10, 11, 0xfe, 0xff], &mut buf).unwrap(), "0a0bfeff");
+        assert_eq!(to_hex_into(&[], &mut buf).unwrap(), "");
+        assert!(match to_hex_into(&[0; 6], &mut buf) {
+            Err(HexError::BufferTooSmall {
+                required: 12,
+                actual: 10,
+            }) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn multiple() {
+        let keys = vec![vec![0u8, 1], vec![], vec![255]];
+        let hexes = to_hex_multiple(&keys);
+        assert_eq!(hexes, vec!["0001", "", "ff"]);
+        assert_eq!(from_hex_multiple(&hexes).unwrap(), keys);
+        assert_eq!(to_hex_multiple(&[[1u8; 32]]), vec!["01".repeat(32)]);
+        let err = from_hex_multiple(&["00", "0g", "1"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Item 1 of the batch is invalid: Only hexadecimal characters (0-9,a-f) are permitted, but found 'g' at \
+             position 1"
+        );
+        assert!(match err {
+            HexError::InvalidItem { index: 1, source } => match *source {
+                HexError::InvalidCharacter {
+                    index: 1,
+                    character: 'g',
+                } => true,
+                _ => false,
+            },
+            _ => false,
+        });
+        let empty: [&str; 0] = [];
+        assert!(from_hex_multiple(&empty).unwrap().is_empty());
+
+        let hashes = vec![[0xaau8; 2], [0xbb; 2]];
+        assert_eq!(to_hex_iter(hashes.iter()), vec!["aaaa", "bbbb"]);
+        assert_eq!(to_hex_iter(vec!["aa", "b"]), vec!["6161", "62"]);
+        assert_eq!(to_hex_iter(Vec::<Vec<u8>>::new()), Vec::<String>::new());
+        assert_eq!(to_hex_joined(&hashes, ", "), "aaaa, bbbb");
+        assert_eq!(to_hex_joined(&[[1u8]], ","), "01");
+        assert_eq!(to_hex_joined(Vec::<Vec<u8>>::new(), ","), "");
+
+        assert_eq!(try_to_hex_multiple(&hashes, Some(2)).unwrap(), vec!["aaaa", "bbbb"]);
+        assert!(match try_to_hex_multiple(&[vec![1], vec![1, 2, 3]], Some(2)) {
+            Err(HexError::InputTooLarge { max: 2, actual: 3 }) => true,
+            _ => false,
+        });
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn multiple_par() {
+        let keys = (0..1000u32).map(|i| i.to_le_bytes().to_vec()).collect::<Vec<_>>();
+        let hexes = to_hex_multiple_par(&keys);
+        assert_eq!(hexes, to_hex_multiple(&keys));
+        assert_eq!(from_hex_multiple_par(&hexes).unwrap(), keys);
+        assert!(match from_hex_multiple_par(&["00", "0g"]) {
+            Err(HexError::InvalidItem { index: 1, .. }) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn lazy_decoding() {
+        for hex in &["", "0x00ff", " 1a2B3c\n", "deadbeef"] {
+            let bytes = decode_iter(hex).collect::<Result<Vec<u8>, _>>().unwrap();
+            assert_eq!(bytes, from_hex(hex).unwrap());
+        }
+        let mut it = decode_iter("0x01zz03");
+        assert_eq!(it.size_hint(), (0, Some(3)));
+        assert_eq!(it.next().unwrap().unwrap(), 1);
+        assert!(match it.next() {
+            Some(Err(HexError::InvalidCharacter {
+                index: 4,
+                character: 'z',
+            })) => true,
+            _ => false,
+        });
+        assert!(it.next().is_none());
+
+        let mut it = decode_iter("abc");
+        assert!(match it.next() {
+            Some(Err(HexError::LengthError { length: 3 })) => true,
+            _ => false,
+        });
+        assert!(it.next().is_none());
+        assert_eq!(decode_iter("0a0b").fold(0u8, |acc, b| acc + b.unwrap()), 21);
+    }
+
+    #[test]
+    fn streaming() {
+        let data = (0..20_000u32).map(|i| (i * 7) as u8).collect::<Vec<u8>>();
+        let mut hex = Vec::new();
+        assert_eq!(encode_to_writer(&mut data.as_slice(), &mut hex).unwrap(), 20_000);
+        assert_eq!(hex, to_hex(&data).into_bytes());
+
+        let mut decoded = Vec::new();
+        assert_eq!(decode_from_reader(&mut hex.as_slice(), &mut decoded).unwrap(), 20_000);
+        assert_eq!(decoded, data);
+
+        let mut decoded = Vec::new();
+        decode_from_reader(&mut &b"0a0B\n0c0\r\nd\n"[..], &mut decoded).unwrap();
+        assert_eq!(decoded, [10, 11, 12, 13]);
+        assert!(match decode_from_reader(&mut &b"0a0"[..], &mut Vec::new()) {
+            Err(HexError::LengthError { length: 3 }) => true,
+            _ => false,
+        });
+        assert!(match decode_from_reader(&mut &b"0a0g"[..], &mut Vec::new()) {
+            Err(HexError::InvalidCharacter {
+                index: 3,
+                character: 'g',
+            }) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn streaming_prefix() {
+        let mut decoded = Vec::new();
+        assert_eq!(decode_from_reader(&mut &b"0xab0c"[..], &mut decoded).unwrap(), 2);
+        assert_eq!(decoded, [0xab, 0x0c]);
+
+        // The prefix is split across two reads
+        let mut decoded = Vec::new();
+        let mut reader = io::Read::chain(&b"0"[..], &b"xab"[..]);
+        assert_eq!(decode_from_reader(&mut reader, &mut decoded).unwrap(), 1);
+        assert_eq!(decoded, [0xab]);
+
+        assert!(match decode_from_reader(&mut &b"0x0xab"[..], &mut Vec::new()) {
+            Err(HexError::InvalidCharacter {
+                index: 3,
+                character: 'x',
+            }) => true,
+            _ => false,
+        });
+        assert!(match decode_from_reader(&mut &b"ab0x"[..], &mut Vec::new()) {
+            Err(HexError::InvalidCharacter {
+                index: 3,
+                character: 'x',
+            }) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn hex_fmt() {
+        let data = (0..100u8).collect::<Vec<u8>>();
+        assert_eq!(format!("{}", HexFmt(&data)), to_hex(&data));
+        assert_eq!(format!("{:x}", HexFmt(&[0xab, 0x01])), "ab01");
+        assert_eq!(format!("{:X}", HexFmt(&[0xab, 0x01])), "AB01");
+        assert_eq!(format!("{:#x}", HexFmt(&[0xab, 0x01])), "0xab01");
+        assert_eq!(format!("{}", HexFmt(&[])), "");
+    }
+
+    #[test]
+    fn hex_dump() {
+        assert_eq!(dump(&[]), "");
+        let data = (0..20u8).chain(b"~ A".iter().cloned()).collect::<Vec<u8>>();
+        assert_eq!(
+            dump(&data),
+            "00000000: 0001 0203 0405 0607 0809 0a0b 0c0d 0e0f  ................\n\
+             00000010: 1011 1213 7e20 41                        ....~ A\n"
+        );
+        assert_eq!(format!("{}", HexDump(&data)), dump(&data));
+    }
+
+    #[test]
+    fn checksummed() {
+        let key = from_hex("0a0b0c0d").unwrap();
+        let hex = to_hex_checksummed(&key);
+        assert_eq!(hex, "0a0b0c0d90a8e328");
+        assert_eq!(from_hex_checksummed(&hex).unwrap(), key);
+        assert_eq!(
+            from_hex_checksummed(&to_hex_checksummed(&[])).unwrap(),
+            Vec::<u8>::new()
+        );
+        // A single transposed digit
+        assert!(match from_hex_checksummed("a00b0c0d90a8e328") {
+            Err(HexError::ChecksumMismatch) => true,
+            _ => false,
+        });
+        assert!(from_hex_checksummed("0a0b").is_err());
+        assert!(from_hex_checksummed("0a0b0c0d90a8e32").is_err());
+    }
+
+    #[test]
+    fn decode_every_digit_pair() {
+        for byte in 0..=255u8 {
+            let lower = format!("{:02x}", byte);
+            assert_eq!(from_hex(&lower).unwrap(), vec![byte]);
+            assert_eq!(from_hex(&lower.to_uppercase()).unwrap(), vec![byte]);
+        }
+        // `from_str_radix` accepts a leading sign, but it is not a hex digit
+        assert!(from_hex("+f").is_err());
+    }
+
+    #[test]
+    fn fixed() {
+        let hex = "0x".to_string() + &"ab".repeat(32);
+        assert_eq!(from_hex_fixed::<[u8; 32]>(&hex).unwrap(), [0xab; 32]);
+        assert_eq!(from_hex_fixed::<[u8; 0]>("").unwrap(), [0u8; 0]);
+        assert!(match from_hex_fixed::<[u8; 2]>("abc") {
+            Err(HexError::LengthError { length: 3 }) => true,
+            _ => false,
+        });
+        assert!(match from_hex_fixed::<[u8; 2]>("0xabcdef") {
+            Err(HexError::LengthError { length: 6 }) => true,
+            _ => false,
+        });
+        let mut signature = [0u8; 64];
+        from_hex_into(&"cd".repeat(64), &mut signature).unwrap();
+        assert!(signature.iter().all(|&b| b == 0xcd));
+        assert!(from_hex_into(&"cd".repeat(63), &mut signature).is_err());
+        assert!(match from_hex_fixed::<[u8; 2]>("ab0g") {
+            Err(HexError::InvalidCharacter {
+                index: 3,
+                character: 'g',
+            }) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(0x0102u16.to_hex(), "0102");
+        assert_eq!(0x0102u16.to_hex_le(), "0201");
+        assert_eq!(1u64.to_hex(), "0000000000000001");
+        assert_eq!(1u64.to_hex_le(), "0100000000000000");
+        assert_eq!(u32::from_hex("deadbeef").unwrap(), 0xdead_beef);
+        assert_eq!(u32::from_hex_le("efbeadde").unwrap(), 0xdead_beef);
+        assert_eq!(u128::from_hex(&u128::max_value().to_hex()).unwrap(), u128::max_value());
+        // Integers are fixed width
+        assert!(u32::from_hex("beef").is_err());
+    }
+
+    #[cfg(feature = "clear_on_drop")]
+    #[test]
+    fn constant_time() {
+        for c in 0..=255u8 {
+            let (value, valid) = ct_hex_digit_value(c);
+            match HEX_VALUES[usize::from(c)] {
+                INVALID_DIGIT => assert_eq!(valid, 0, "{}", c),
+                v => assert_eq!((value, valid), (v, 0xff), "{}", c),
+            }
+        }
+        let key = from_hex_ct(" 00fFa5E7 ").unwrap();
+        assert_eq!(&key[..], &[0x00, 0xff, 0xa5, 0xe7]);
+        assert_eq!(
+            &from_hex_ct(" 0x00fFa5E7").unwrap()[..],
+            &from_hex("0x00fFa5E7").unwrap()[..]
+        );
+        assert!(from_hex_ct("0x").unwrap().is_empty());
+        assert!(from_hex_ct("0x0x00").is_err());
+        assert!(match from_hex_ct("00g0") {
+            Err(HexError::HexConversionError) => true,
+            _ => false,
+        });
+        assert!(match from_hex_ct("abc") {
+            Err(HexError::LengthError { length: 3 }) => true,
+            _ => false,
+        });
+        assert!(from_hex_ct("00é0").is_err());
+    }
+
+    #[test]
+    fn prefixed() {
+        assert_eq!(to_hex_prefixed(&[0xde, 0xad]), "0xdead");
+        assert_eq!(to_hex_prefixed(&[]), "0x");
+        assert_eq!(from_hex(&to_hex_prefixed(&[0xde, 0xad])).unwrap(), vec![0xde, 0xad]);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn prefixed_serde() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Tx {
+            #[serde(
+                serialize_with = "serialize_to_hex_prefixed",
+                deserialize_with = "deserialize_from_hex"
+            )]
+            hash: Vec<u8>,
+        }
+        let tx = Tx { hash: vec![0xbe, 0xef] };
+        let json = serde_json::to_string(&tx).unwrap();
+        assert_eq!(json, r#"{"hash":"0xbeef"}"#);
+        assert_eq!(serde_json::from_str::<Tx>(&json).unwrap(), tx);
+        assert_eq!(serde_json::from_str::<Tx>(r#"{"hash":"beef"}"#).unwrap(), tx);
+        assert!(serde_json::from_str::<Tx>(r#"{"hash":"0xbee"}"#).is_err());
+    }
+
+    #[cfg(feature = "tiny-keccak")]
+    #[test]
+    fn mixed_case_checksum() {
+        // Test vectors from EIP-55
+        for address in &[
+            "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "fB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "dbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "D1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let bytes = from_hex(address).unwrap();
+            assert_eq!(&to_hex_mixed_case_checksum(&bytes), address);
+            assert_eq!(validate_hex_checksum(address).unwrap(), bytes);
+            assert_eq!(validate_hex_checksum(&format!("0x{}", address)).unwrap(), bytes);
+            assert!(match validate_hex_checksum(&address.to_lowercase()) {
+                Err(HexError::ChecksumMismatch) => true,
+                _ => false,
+            });
+        }
+        // A typo in a digit changes the case of the letters
+        assert!(validate_hex_checksum("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAee").is_err());
+    }
+
+    #[test]
+    fn lenient() {
+        assert_eq!(from_hex_lenient("de:ad:be:ef").unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(from_hex_lenient(" 0xDE AD_be\tef\n").unwrap(), vec![
+            0xde, 0xad, 0xbe, 0xef
+        ]);
+        assert_eq!(from_hex_lenient("dead").unwrap(), from_hex("dead").unwrap());
+        assert_eq!(from_hex_lenient("::").unwrap(), Vec::<u8>::new());
+        assert!(match from_hex_lenient("de:a:d") {
+            Err(HexError::InvalidCharacter {
+                index: 4,
+                character: ':',
+            }) => true,
+            _ => false,
+        });
+        assert!(match from_hex_lenient("de:ad:b") {
+            Err(HexError::LengthError { length: 5 }) => true,
+            _ => false,
+        });
+        assert!(from_hex_lenient("de-ad").is_err());
+    }
+
+    #[test]
+    fn uuid() {
+        let bytes = from_hex_fixed::<[u8; 16]>("123e4567e89b12d3a456426614174000").unwrap();
+        assert_eq!(to_uuid_string(&bytes), "123e4567-e89b-12d3-a456-426614174000");
+        assert_eq!(from_uuid_string("123E4567-E89B-12D3-A456-426614174000").unwrap(), bytes);
+        assert!(match from_uuid_string("123e4567e89b12d3a456426614174000") {
+            Err(HexError::HexConversionError) => true,
+            _ => false,
+        });
+        assert!(match from_uuid_string("123e4567-e89b-12d3-a456-42661417400") {
+            Err(HexError::HexConversionError) => true,
+            _ => false,
+        });
+        assert!(match from_uuid_string("123e4567-e89b-12d3-a456-4266141740g0") {
+            Err(HexError::InvalidCharacter {
+                index: 34,
+                character: 'g',
+            }) => true,
+            _ => false,
+        });
+        assert!(match from_uuid_string("123e4567-e89b-12d3-a456-4266141740é") {
+            Err(HexError::InvalidCharacter { index: 34, .. }) => true,
+            _ => false,
+        });
+    }
+
     #[test]
     fn length_error() {
         let result = from_hex(&"800");
         assert!(result.is_err());
         let err = result.unwrap_err();
         match err {
-            HexError::LengthError => (),
+            HexError::LengthError { length: 3 } => (),
             _ => panic!(),
         }
         // Check that message is the doc message above
-        assert_eq!(err.to_string(), "Hex string lengths must be a multiple of 2");
+        assert_eq!(
+            err.to_string(),
+            "Hex string lengths must be a multiple of 2 and fit the target type, but the string has 3 digits"
+        );
     }
 
     #[test]
@@ -122,9 +1258,53 @@ mod test {
         assert!(result.is_err());
         let err = result.unwrap_err();
         match &err {
-            HexError::InvalidCharacter(e) => println!("{:?}", e),
+            HexError::InvalidCharacter {
+                index: 16,
+                character: 'G',
+            } => (),
             _ => panic!(),
         }
-        assert_eq!(err.to_string(), "Only hexadecimal characters (0-9,a-f) are permitted");
+        assert_eq!(
+            err.to_string(),
+            "Only hexadecimal characters (0-9,a-f) are permitted, but found 'G' at position 16"
+        );
+    }
+
+    #[test]
+    fn character_error_positions() {
+        // Positions count from the start of the original string, including whitespace and any 0x prefix
+        assert!(match from_hex("  0x00z0") {
+            Err(HexError::InvalidCharacter {
+                index: 6,
+                character: 'z',
+            }) => true,
+            _ => false,
+        });
+        assert!(match from_hex("00é0") {
+            Err(HexError::InvalidCharacter {
+                index: 2,
+                character: 'é',
+            }) => true,
+            _ => false,
+        });
+        assert!(match decode_from_reader(&mut &b"00\n0\xff"[..], &mut Vec::new()) {
+            Err(HexError::InvalidCharacter { index: 4, character }) => character == std::char::REPLACEMENT_CHARACTER,
+            _ => false,
+        });
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_hex() {
+        let data = (0..4096u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect::<Vec<u8>>();
+        let mut u = Unstructured::new(&data);
+        for _ in 0..32 {
+            let ValidHex(valid) = ValidHex::arbitrary(&mut u).unwrap();
+            assert!(from_hex(&valid).is_ok(), "{} should decode", valid);
+            let InvalidHex(invalid) = InvalidHex::arbitrary(&mut u).unwrap();
+            assert!(from_hex(&invalid).is_err(), "{} should not decode", invalid);
+        }
     }
 }
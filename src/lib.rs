@@ -20,24 +20,55 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+//! Each optional module is behind a cargo feature of the same name, and all of them are enabled by default.
+//! `byte_array`, `checksum`, `error`, `extend_bytes`, `hash`, `hex`, `newtype` and `serde` are the always-on core that
+//! the other modules build on, so they can't be disabled. The `Digest` helpers in `hash`, and
+//! `MessageFormat::canonical_hash`, need the `digest` feature.
+
+#[cfg(feature = "bit")]
 #[allow(clippy::needless_range_loop)]
 pub mod bit;
 pub mod byte_array;
+#[cfg(feature = "byte_cursor")]
+pub mod byte_cursor;
+pub mod checksum;
+#[cfg(feature = "chunking")]
+pub mod chunking;
+#[cfg(feature = "ciphers")]
 pub mod ciphers;
+#[cfg(feature = "convert")]
 pub mod convert;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+#[cfg(feature = "epoch_time")]
 pub mod epoch_time;
+pub mod error;
 pub mod extend_bytes;
+#[cfg(feature = "fixed_set")]
 pub mod fixed_set;
 pub mod hash;
 pub mod hex;
+#[cfg(feature = "locks")]
 #[macro_use]
 pub mod locks;
+#[cfg(feature = "message_format")]
 pub mod message_format;
+#[macro_use]
+pub mod newtype;
+#[cfg(feature = "percentage")]
+pub mod percentage;
+pub mod serde;
+#[cfg(feature = "string")]
+pub mod string;
+#[cfg(all(feature = "thread_join", not(target_arch = "wasm32")))]
 pub mod thread_join;
+#[cfg(feature = "u256")]
+pub mod u256;
 
 pub use self::extend_bytes::ExtendBytes;
 
 pub use self::{
-    byte_array::{ByteArray, ByteArrayError},
+    byte_array::{ByteArray, ByteArrayError, ByteArrayRef, FixedByteArray},
+    error::TariUtilError,
     hash::Hashable,
 };
@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::sync::{Mutex, RwLock};
+
 /// Recovers a poisoned lock by returning the value before the lock was poisoned
 #[macro_export]
 macro_rules! recover_lock {
@@ -63,3 +65,84 @@ macro_rules! acquire_read_lock {
         $crate::acquire_lock!($e, read)
     };
 }
+
+/// Run `f` with a read lock on the given `RwLock`, silently recovering the lock if it is poisoned. The lock is released
+/// as soon as `f` returns, so the guard can never outlive the closure (e.g. by being held across an `.await`).
+pub fn with_read<T, R, F>(lock: &RwLock<T>, f: F) -> R
+where F: FnOnce(&T) -> R {
+    let guard = acquire_read_lock!(lock);
+    f(&*guard)
+}
+
+/// Run `f` with a write lock on the given `RwLock`, silently recovering the lock if it is poisoned. The lock is
+/// released as soon as `f` returns.
+pub fn with_write<T, R, F>(lock: &RwLock<T>, f: F) -> R
+where F: FnOnce(&mut T) -> R {
+    let mut guard = acquire_write_lock!(lock);
+    f(&mut *guard)
+}
+
+/// Run `f` with the given `Mutex` locked, silently recovering the lock if it is poisoned. The lock is released as soon
+/// as `f` returns.
+pub fn with_lock<T, R, F>(lock: &Mutex<T>, f: F) -> R
+where F: FnOnce(&mut T) -> R {
+    let mut guard = acquire_lock!(lock);
+    f(&mut *guard)
+}
+
+/// Macro form of [with_read](locks/fn.with_read.html): `with_read_lock!(lock, |v| ...)`
+#[macro_export]
+macro_rules! with_read_lock {
+    ($lock:expr, $f:expr) => {
+        $crate::locks::with_read(&$lock, $f)
+    };
+}
+
+/// Macro form of [with_write](locks/fn.with_write.html): `with_write_lock!(lock, |v| ...)`
+#[macro_export]
+macro_rules! with_write_lock {
+    ($lock:expr, $f:expr) => {
+        $crate::locks::with_write(&$lock, $f)
+    };
+}
+
+/// Macro form of [with_lock](locks/fn.with_lock.html): `with_lock!(mutex, |v| ...)`
+#[macro_export]
+macro_rules! with_lock {
+    ($lock:expr, $f:expr) => {
+        $crate::locks::with_lock(&$lock, $f)
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn closures_see_and_update_the_value() {
+        let lock = RwLock::new(vec![1, 2]);
+        with_write(&lock, |v| v.push(3));
+        assert_eq!(with_read(&lock, |v| v.len()), 3);
+        with_write_lock!(lock, |v: &mut Vec<i32>| v.clear());
+        assert!(with_read_lock!(lock, |v: &Vec<i32>| v.is_empty()));
+
+        let mutex = Mutex::new(1);
+        assert_eq!(with_lock(&mutex, |v| std::mem::replace(v, 2)), 1);
+        assert_eq!(with_lock!(mutex, |v: &mut i32| *v), 2);
+    }
+
+    #[test]
+    fn poisoned_locks_are_recovered() {
+        let lock = Arc::new(RwLock::new(1));
+        let lock2 = lock.clone();
+        let _ = thread::spawn(move || with_write(&lock2, |_| panic!("poison"))).join();
+        assert!(lock.is_poisoned());
+        assert_eq!(with_read(&lock, |v| *v), 1);
+
+        let mutex = Arc::new(Mutex::new(1));
+        let mutex2 = mutex.clone();
+        let _ = thread::spawn(move || with_lock(&mutex2, |_| panic!("poison"))).join();
+        assert_eq!(with_lock(&mutex, |v| *v), 1);
+    }
+}
@@ -20,9 +20,26 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod canonical;
+mod compression;
+pub mod envelope;
+#[cfg(feature = "prost")]
+mod protobuf;
+
+#[cfg(feature = "prost")]
+pub use self::protobuf::Protobuf;
+pub use self::{
+    compression::{Compression, MAX_DECOMPRESSED_SIZE},
+    envelope::{Envelope, EnvelopeError, PayloadFormat},
+};
+#[cfg(feature = "digest")]
+use crate::hash::Output;
 use base64;
+#[cfg(feature = "digest")]
+use digest::Digest;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json;
+use std::io::{Read, Write};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -35,16 +52,126 @@ pub enum MessageFormatError {
     JSONError(#[from] serde_json::error::Error),
     #[error("An error occurred deserialising an object from Base64")]
     Base64DeserializeError(#[from] base64::DecodeError),
+    #[error("JSON can't represent NaN or infinite numbers")]
+    NonFiniteFloat,
+    #[error("Unknown or disabled compression algorithm {0}")]
+    UnsupportedCompression(u8),
+    #[error("The message decompresses to more than {0} bytes")]
+    DecompressedTooLarge(usize),
+    #[error("{0}")]
+    EnvelopeError(#[from] EnvelopeError),
+}
+
+/// The base64 alphabet and padding used by [MessageFormat::to_base64_with] and [MessageFormat::from_base64_with]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Variant {
+    /// The standard alphabet with padding, as used by `to_base64`
+    Standard,
+    /// The standard alphabet without padding
+    StandardNoPad,
+    /// The URL-safe alphabet (`-` and `_` instead of `+` and `/`) with padding
+    UrlSafe,
+    /// The URL-safe alphabet without padding, e.g. for tokens embedded in URLs and QR codes
+    UrlSafeNoPad,
+}
+
+impl Base64Variant {
+    fn config(self) -> base64::Config {
+        match self {
+            Base64Variant::Standard => base64::STANDARD,
+            Base64Variant::StandardNoPad => base64::STANDARD_NO_PAD,
+            Base64Variant::UrlSafe => base64::URL_SAFE,
+            Base64Variant::UrlSafeNoPad => base64::URL_SAFE_NO_PAD,
+        }
+    }
 }
 
+/// The largest message, in bytes, that [MessageFormat::from_binary_reader] will read. The binary form carries length
+/// prefixes, so without a cap a few hostile bytes could make the reader allocate gigabytes before finding out that the
+/// data isn't there. Anything larger is rejected with [MessageFormatError::BinaryDeserializeError].
+pub const MAX_READER_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Conversions between a message and its binary, JSON and base64 forms. Implementations only need to provide the
+/// `to_`/`from_` binary, JSON and base64 methods; the others have default implementations built on those.
 pub trait MessageFormat: Sized {
     fn to_binary(&self) -> Result<Vec<u8>, MessageFormatError>;
     fn to_json(&self) -> Result<String, MessageFormatError>;
     fn to_base64(&self) -> Result<String, MessageFormatError>;
+    /// Serialize the message to base64 with the given alphabet and padding
+    fn to_base64_with(&self, variant: Base64Variant) -> Result<String, MessageFormatError> {
+        Ok(base64::encode_config(&self.to_binary()?, variant.config()))
+    }
+
+    /// Serialize the message to JSON in a canonical form, for signing: object keys are sorted, there is no whitespace,
+    /// integral floats are written as integers and NaN and infinite floats are an error. The same value always
+    /// produces the same string, on every platform.
+    ///
+    /// The default implementation rewrites the output of `to_json`, which will already have written any NaN or
+    /// infinite float as `null`.
+    fn to_json_canonical(&self) -> Result<String, MessageFormatError> {
+        let value: serde_json::Value = serde_json::from_str(&self.to_json()?)?;
+        canonical::to_json_canonical(&value)
+    }
+
+    /// Serialize the message to binary and compress it, prefixed with a header recording the algorithm used
+    fn to_compressed_binary(&self, compression: Compression) -> Result<Vec<u8>, MessageFormatError> {
+        compression::to_compressed_binary(self, compression)
+    }
+
+    /// Serialize the message to binary directly into `writer`, without holding the whole encoding in memory. The
+    /// output is the same as `to_binary`. Wrap unbuffered writers such as files in a `BufWriter`.
+    ///
+    /// The default implementation writes the output of `to_binary`, so it holds the whole encoding in memory after
+    /// all.
+    fn to_binary_writer<W: Write>(&self, mut writer: W) -> Result<(), MessageFormatError> {
+        writer
+            .write_all(&self.to_binary()?)
+            .map_err(|_| MessageFormatError::BinarySerializeError)
+    }
 
     fn from_binary(msg: &[u8]) -> Result<Self, MessageFormatError>;
     fn from_json(msg: &str) -> Result<Self, MessageFormatError>;
     fn from_base64(msg: &str) -> Result<Self, MessageFormatError>;
+    /// Deserialize a message written by `to_base64_with` with the same variant
+    fn from_base64_with(msg: &str, variant: Base64Variant) -> Result<Self, MessageFormatError> {
+        Self::from_binary(&base64::decode_config(msg, variant.config())?)
+    }
+    /// Read a message written by `to_compressed_binary`, with whichever algorithm it names
+    fn from_compressed_binary(msg: &[u8]) -> Result<Self, MessageFormatError> {
+        compression::from_compressed_binary(msg)
+    }
+    /// Deserialize a message written by `to_binary` or `to_binary_writer`, reading it incrementally from `reader`. At
+    /// most [MAX_READER_MESSAGE_SIZE] bytes are read for the message.
+    ///
+    /// The default implementation can't tell where the message ends, so it reads everything up to the end of `reader`
+    /// and passes it to `from_binary`.
+    fn from_binary_reader<R: Read>(reader: R) -> Result<Self, MessageFormatError> {
+        let mut buf = Vec::new();
+        reader
+            .take(MAX_READER_MESSAGE_SIZE as u64 + 1)
+            .read_to_end(&mut buf)
+            .map_err(|_| MessageFormatError::BinaryDeserializeError)?;
+        if buf.len() > MAX_READER_MESSAGE_SIZE {
+            return Err(MessageFormatError::BinaryDeserializeError);
+        }
+        Self::from_binary(&buf)
+    }
+
+    /// Return the number of bytes that `to_binary` would produce, without actually serializing the message. This can
+    /// be used to check frame size limits or to pre-allocate buffers. Returns `None` if the size cannot be determined,
+    /// which is what the default implementation always does.
+    fn binary_size_hint(&self) -> Option<usize> {
+        None
+    }
+
+    /// Hash the canonical JSON form of the message, as produced by `to_json_canonical`, with the digest `D`. Use this
+    /// wherever a "hash of this message" is needed, e.g. for dedup caches or signing, so that every crate hashes the
+    /// same bytes. The binary form isn't used because it writes maps such as `HashMap` in iteration order, so equal
+    /// messages could hash differently.
+    #[cfg(feature = "digest")]
+    fn canonical_hash<D: Digest>(&self) -> Result<Output<D>, MessageFormatError> {
+        Ok(D::digest(self.to_json_canonical()?.as_bytes()))
+    }
 }
 
 impl<T> MessageFormat for T
@@ -59,8 +186,15 @@ where T: DeserializeOwned + Serialize
     }
 
     fn to_base64(&self) -> Result<String, MessageFormatError> {
-        let val = self.to_binary()?;
-        Ok(base64::encode(&val))
+        self.to_base64_with(Base64Variant::Standard)
+    }
+
+    fn to_json_canonical(&self) -> Result<String, MessageFormatError> {
+        canonical::to_json_canonical(self)
+    }
+
+    fn to_binary_writer<W: Write>(&self, writer: W) -> Result<(), MessageFormatError> {
+        bincode::serialize_into(writer, self).map_err(|_| MessageFormatError::BinarySerializeError)
     }
 
     fn from_binary(msg: &[u8]) -> Result<Self, MessageFormatError> {
@@ -73,8 +207,18 @@ where T: DeserializeOwned + Serialize
     }
 
     fn from_base64(msg: &str) -> Result<Self, MessageFormatError> {
-        let buf = base64::decode(msg)?;
-        Self::from_binary(&buf)
+        Self::from_base64_with(msg, Base64Variant::Standard)
+    }
+
+    fn from_binary_reader<R: Read>(reader: R) -> Result<Self, MessageFormatError> {
+        bincode::config()
+            .limit(MAX_READER_MESSAGE_SIZE as u64)
+            .deserialize_from(reader)
+            .map_err(|_| MessageFormatError::BinaryDeserializeError)
+    }
+
+    fn binary_size_hint(&self) -> Option<usize> {
+        bincode::serialized_size(self).ok().map(|size| size as usize)
     }
 }
 
@@ -83,6 +227,7 @@ mod test {
     use super::*;
     use base64::DecodeError as Base64Error;
     use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
 
     #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
     struct TestMessage {
@@ -117,6 +262,63 @@ mod test {
         assert_eq!(val, val2);
     }
 
+    #[test]
+    fn binary_stream() {
+        let mut val = TestMessage::new("tomorrow", 50);
+        val.set_sub_message(TestMessage::new("today", 100));
+        let mut buf = Vec::new();
+        val.to_binary_writer(&mut buf).unwrap();
+        val.to_binary_writer(&mut buf).unwrap();
+        assert_eq!(&buf[..buf.len() / 2], &val.to_binary().unwrap()[..]);
+
+        let mut reader = std::io::Cursor::new(buf);
+        assert_eq!(TestMessage::from_binary_reader(&mut reader).unwrap(), val);
+        assert_eq!(TestMessage::from_binary_reader(&mut reader).unwrap(), val);
+        assert!(matches!(
+            TestMessage::from_binary_reader(&mut reader),
+            Err(MessageFormatError::BinaryDeserializeError)
+        ));
+    }
+
+    #[test]
+    fn binary_stream_hostile_length() {
+        // The key's length prefix claims a terabyte; this must fail rather than try to allocate it
+        let hostile = [0xffu8, 0xff, 0xff, 0xff, 0xff, 0, 0, 0];
+        assert!(matches!(
+            TestMessage::from_binary_reader(&hostile[..]),
+            Err(MessageFormatError::BinaryDeserializeError)
+        ));
+        assert!(TestMessage::from_binary(&hostile).is_err());
+    }
+
+    #[test]
+    fn binary_size_hint() {
+        let val = TestMessage::new("twenty", 20);
+        assert_eq!(val.binary_size_hint(), Some(23));
+        let mut val = TestMessage::new("tomorrow", 50);
+        val.set_sub_message(TestMessage::new("today", 100));
+        assert_eq!(val.binary_size_hint(), Some(val.to_binary().unwrap().len()));
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn canonical_hash() {
+        use sha2::Sha256;
+        let val = TestMessage::new("twenty", 20);
+        let hash = val.canonical_hash::<Sha256>().unwrap();
+        assert_eq!(hash, Sha256::digest(val.to_json_canonical().unwrap().as_bytes()));
+        assert_eq!(hash, TestMessage::new("twenty", 20).canonical_hash::<Sha256>().unwrap());
+        assert_ne!(hash, TestMessage::new("twenty", 21).canonical_hash::<Sha256>().unwrap());
+
+        // The same entries inserted in a different order must hash the same, whatever the map's iteration order
+        let forwards = (0..100).map(|i| (i, i)).collect::<HashMap<u32, u32>>();
+        let backwards = (0..100).rev().map(|i| (i, i)).collect::<HashMap<u32, u32>>();
+        assert_eq!(
+            forwards.canonical_hash::<Sha256>().unwrap(),
+            backwards.canonical_hash::<Sha256>().unwrap()
+        );
+    }
+
     #[test]
     fn base64_simple() {
         let val = TestMessage::new("twenty", 20);
@@ -126,6 +328,27 @@ mod test {
         assert_eq!(val, val2);
     }
 
+    #[test]
+    fn base64_url_safe() {
+        let val = TestMessage::new("???~~~", 20);
+        assert_eq!(val.to_base64().unwrap(), "BgAAAAAAAAA/Pz9+fn4UAAAAAAAAAAA=");
+        let msg = val.to_base64_with(Base64Variant::UrlSafeNoPad).unwrap();
+        assert_eq!(msg, "BgAAAAAAAAA_Pz9-fn4UAAAAAAAAAAA");
+        assert_eq!(
+            TestMessage::from_base64_with(&msg, Base64Variant::UrlSafeNoPad).unwrap(),
+            val
+        );
+        assert!(TestMessage::from_base64(&msg).is_err());
+        assert_eq!(
+            val.to_base64_with(Base64Variant::StandardNoPad).unwrap(),
+            "BgAAAAAAAAA/Pz9+fn4UAAAAAAAAAAA"
+        );
+        assert_eq!(
+            val.to_base64_with(Base64Variant::UrlSafe).unwrap(),
+            "BgAAAAAAAAA_Pz9-fn4UAAAAAAAAAAA="
+        );
+    }
+
     #[test]
     fn json_simple() {
         let val = TestMessage::new("twenty", 20);
@@ -135,6 +358,54 @@ mod test {
         assert_eq!(val, val2);
     }
 
+    #[test]
+    fn json_canonical() {
+        #[derive(Serialize, Deserialize)]
+        struct Prices {
+            zulu: f64,
+            alpha: Vec<f32>,
+            mid: HashMap<String, f64>,
+        }
+
+        let mut mid = HashMap::new();
+        mid.insert("b".to_string(), -0.0);
+        mid.insert("a".to_string(), 0.1);
+        let prices = Prices {
+            zulu: 3.0,
+            alpha: vec![1.5, 2.0],
+            mid,
+        };
+        assert_eq!(
+            prices.to_json_canonical().unwrap(),
+            r#"{"alpha":[1.5,2],"mid":{"a":0.1,"b":0},"zulu":3}"#
+        );
+
+        let nan = Prices {
+            zulu: std::f64::NAN,
+            alpha: vec![],
+            mid: HashMap::new(),
+        };
+        assert!(matches!(
+            nan.to_json_canonical(),
+            Err(MessageFormatError::NonFiniteFloat)
+        ));
+        let infinite = Prices {
+            zulu: 0.0,
+            alpha: vec![std::f32::INFINITY],
+            mid: HashMap::new(),
+        };
+        assert!(matches!(
+            infinite.to_json_canonical(),
+            Err(MessageFormatError::NonFiniteFloat)
+        ));
+
+        let nested = serde_json::json!({"b": [{"z": 1, "é": "x\"y", "a": {}}], "a": null});
+        assert_eq!(
+            nested.to_json_canonical().unwrap(),
+            r#"{"a":null,"b":[{"a":{},"z":1,"é":"x\"y"}]}"#
+        );
+    }
+
     #[test]
     fn nested_message() {
         let inner = TestMessage::new("today", 100);
@@ -211,4 +482,48 @@ mod test {
             },
         }
     }
+
+    #[test]
+    fn default_methods() {
+        // A manual implementation that only provides the required methods
+        #[derive(Debug, PartialEq)]
+        struct Raw(Vec<u8>);
+
+        impl MessageFormat for Raw {
+            fn to_binary(&self) -> Result<Vec<u8>, MessageFormatError> {
+                Ok(self.0.clone())
+            }
+
+            fn to_json(&self) -> Result<String, MessageFormatError> {
+                Ok(serde_json::json!({ "raw": self.0, "a": 1 }).to_string())
+            }
+
+            fn to_base64(&self) -> Result<String, MessageFormatError> {
+                Ok(base64::encode(&self.0))
+            }
+
+            fn from_binary(msg: &[u8]) -> Result<Self, MessageFormatError> {
+                Ok(Raw(msg.to_vec()))
+            }
+
+            fn from_json(_msg: &str) -> Result<Self, MessageFormatError> {
+                unimplemented!()
+            }
+
+            fn from_base64(msg: &str) -> Result<Self, MessageFormatError> {
+                Ok(Raw(base64::decode(msg)?))
+            }
+        }
+
+        let raw = Raw(vec![0xfb, 0xff]);
+        assert_eq!(raw.to_base64_with(Base64Variant::UrlSafeNoPad).unwrap(), "-_8");
+        assert_eq!(Raw::from_base64_with("-_8", Base64Variant::UrlSafeNoPad).unwrap(), raw);
+        assert_eq!(raw.to_json_canonical().unwrap(), r#"{"a":1,"raw":[251,255]}"#);
+        let compressed = raw.to_compressed_binary(Compression::None).unwrap();
+        assert_eq!(Raw::from_compressed_binary(&compressed).unwrap(), raw);
+        let mut buf = Vec::new();
+        raw.to_binary_writer(&mut buf).unwrap();
+        assert_eq!(Raw::from_binary_reader(&buf[..]).unwrap(), raw);
+        assert_eq!(raw.binary_size_hint(), None);
+    }
 }
@@ -0,0 +1,328 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Canonical JSON for [MessageFormat::to_json_canonical](super::MessageFormat::to_json_canonical): object keys are
+//! sorted, there is no insignificant whitespace, floats with integral values are written as integers and NaN and the
+//! infinities are rejected rather than silently written as `null`.
+
+use super::MessageFormatError;
+use serde::{ser, Serialize};
+use serde_json::{Number, Value};
+use std::fmt;
+
+/// The largest integer up to which every integer is exactly representable as an `f64`
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+pub(super) fn to_json_canonical<T: Serialize>(value: &T) -> Result<String, MessageFormatError> {
+    match value.serialize(FiniteCheck) {
+        Err(CheckError::NonFinite) => return Err(MessageFormatError::NonFiniteFloat),
+        // Any other failure is reported by serde_json below
+        Err(CheckError::Custom) | Ok(()) => {},
+    }
+    let mut value = serde_json::to_value(value)?;
+    normalize_numbers(&mut value);
+    let mut out = String::new();
+    write_sorted(&value, &mut out)?;
+    Ok(out)
+}
+
+/// Write `value` without whitespace, with the keys of every object in byte order. `serde_json::Map` is only sorted
+/// when serde_json's `preserve_order` feature is off, and any crate in the build can turn it on, so the keys are
+/// sorted here rather than relying on the map's order.
+fn write_sorted(value: &Value, out: &mut String) -> Result<(), serde_json::Error> {
+    match value {
+        Value::Array(values) => {
+            out.push('[');
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_sorted(v, out)?;
+            }
+            out.push(']');
+        },
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (k, v)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::to_string(k)?);
+                out.push(':');
+                write_sorted(v, out)?;
+            }
+            out.push('}');
+        },
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => {
+            out.push_str(&serde_json::to_string(value)?)
+        },
+    }
+    Ok(())
+}
+
+fn normalize_numbers(value: &mut Value) {
+    match value {
+        Value::Number(n) => {
+            if let Some(f) = n.as_f64().filter(|_| n.is_f64()) {
+                if f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER {
+                    // Also turns -0.0 into 0
+                    *n = Number::from(f as i64);
+                }
+            }
+        },
+        Value::Array(values) => values.iter_mut().for_each(normalize_numbers),
+        Value::Object(map) => map.values_mut().for_each(normalize_numbers),
+        Value::Null | Value::Bool(_) | Value::String(_) => {},
+    }
+}
+
+#[derive(Debug)]
+enum CheckError {
+    NonFinite,
+    Custom,
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("non-finite float")
+    }
+}
+
+impl std::error::Error for CheckError {}
+
+impl ser::Error for CheckError {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        CheckError::Custom
+    }
+}
+
+/// A serializer that produces nothing, but fails if the value contains a NaN or infinite float
+struct FiniteCheck;
+
+fn check_float(v: f64) -> Result<(), CheckError> {
+    if v.is_finite() {
+        Ok(())
+    } else {
+        Err(CheckError::NonFinite)
+    }
+}
+
+impl ser::Serializer for FiniteCheck {
+    type Error = CheckError;
+    type Ok = ();
+    type SerializeMap = Self;
+    type SerializeSeq = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+
+    fn serialize_f32(self, v: f32) -> Result<(), CheckError> {
+        check_float(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), CheckError> {
+        check_float(v)
+    }
+
+    fn serialize_bool(self, _: bool) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_char(self, _: char) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_str(self, _: &str) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), CheckError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<(), CheckError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), CheckError>
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self, CheckError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, CheckError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, CheckError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self, CheckError> {
+        Ok(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self, CheckError> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, CheckError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self, CheckError> {
+        Ok(self)
+    }
+}
+
+/// Implement one of serde's compound serializer traits for [FiniteCheck] by checking each element
+macro_rules! check_elements {
+    ($($trait:ident :: $method:ident),+) => {
+        $(impl ser::$trait for FiniteCheck {
+            type Error = CheckError;
+            type Ok = ();
+
+            fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), CheckError> {
+                value.serialize(FiniteCheck)
+            }
+
+            fn end(self) -> Result<(), CheckError> {
+                Ok(())
+            }
+        })+
+    };
+}
+
+check_elements!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+impl ser::SerializeMap for FiniteCheck {
+    type Error = CheckError;
+    type Ok = ();
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), CheckError> {
+        key.serialize(FiniteCheck)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), CheckError> {
+        value.serialize(FiniteCheck)
+    }
+
+    fn end(self) -> Result<(), CheckError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for FiniteCheck {
+    type Error = CheckError;
+    type Ok = ();
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _: &'static str, value: &T) -> Result<(), CheckError> {
+        value.serialize(FiniteCheck)
+    }
+
+    fn end(self) -> Result<(), CheckError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for FiniteCheck {
+    type Error = CheckError;
+    type Ok = ();
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _: &'static str, value: &T) -> Result<(), CheckError> {
+        value.serialize(FiniteCheck)
+    }
+
+    fn end(self) -> Result<(), CheckError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Compressed binary messages. The compressed form starts with a one byte header naming the algorithm, so that readers
+//! don't need to know how a message was written and uncompressed messages can still be read.
+
+use super::{MessageFormat, MessageFormatError};
+
+/// The largest binary form, in bytes, that [MessageFormat::from_compressed_binary] will decompress. A few bytes of
+/// compressed input can expand to gigabytes, so anything larger is rejected with
+/// [MessageFormatError::DecompressedTooLarge]. Use [MessageFormat::from_binary_reader] for larger messages.
+pub const MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+/// The compression algorithm used by [MessageFormat::to_compressed_binary]. Which algorithms exist depends on the
+/// enabled features, so the enum is non-exhaustive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// Store the binary form as is
+    None,
+    /// DEFLATE (RFC 1951) at the default level. Requires the `flate2` feature.
+    #[cfg(feature = "flate2")]
+    Deflate,
+}
+
+impl Compression {
+    fn tag(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            #[cfg(feature = "flate2")]
+            Compression::Deflate => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(Compression::None),
+            #[cfg(feature = "flate2")]
+            1 => Some(Compression::Deflate),
+            _ => None,
+        }
+    }
+}
+
+pub(super) fn to_compressed_binary<T: MessageFormat>(
+    msg: &T,
+    compression: Compression,
+) -> Result<Vec<u8>, MessageFormatError>
+{
+    let binary = msg.to_binary()?;
+    let mut out = vec![compression.tag()];
+    match compression {
+        Compression::None => out.extend_from_slice(&binary),
+        #[cfg(feature = "flate2")]
+        Compression::Deflate => {
+            use std::io::Write;
+            let mut encoder = flate2::write::DeflateEncoder::new(out, flate2::Compression::default());
+            encoder
+                .write_all(&binary)
+                .map_err(|_| MessageFormatError::BinarySerializeError)?;
+            out = encoder.finish().map_err(|_| MessageFormatError::BinarySerializeError)?;
+        },
+    }
+    Ok(out)
+}
+
+pub(super) fn from_compressed_binary<T: MessageFormat>(msg: &[u8]) -> Result<T, MessageFormatError> {
+    let (&tag, body) = msg.split_first().ok_or(MessageFormatError::BinaryDeserializeError)?;
+    let compression = Compression::from_tag(tag).ok_or(MessageFormatError::UnsupportedCompression(tag))?;
+    match compression {
+        Compression::None => T::from_binary(body),
+        #[cfg(feature = "flate2")]
+        Compression::Deflate => T::from_binary(&inflate(body, MAX_DECOMPRESSED_SIZE)?),
+    }
+}
+
+/// Decompress DEFLATE data, failing if it expands to more than `limit` bytes
+#[cfg(feature = "flate2")]
+fn inflate(body: &[u8], limit: usize) -> Result<Vec<u8>, MessageFormatError> {
+    use std::io::Read;
+    let mut binary = Vec::new();
+    // Read one byte past the limit to tell data that fits exactly from data that doesn't
+    flate2::read::DeflateDecoder::new(body)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut binary)
+        .map_err(|_| MessageFormatError::BinaryDeserializeError)?;
+    if binary.len() > limit {
+        return Err(MessageFormatError::DecompressedTooLarge(limit));
+    }
+    Ok(binary)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn uncompressed() {
+        let msg = vec![7u64; 100];
+        let bytes = msg.to_compressed_binary(Compression::None).unwrap();
+        assert_eq!(bytes[0], 0);
+        assert_eq!(&bytes[1..], &msg.to_binary().unwrap()[..]);
+        assert_eq!(Vec::<u64>::from_compressed_binary(&bytes).unwrap(), msg);
+    }
+
+    #[test]
+    fn unknown_algorithm() {
+        assert!(matches!(
+            u64::from_compressed_binary(&[9, 1, 2]),
+            Err(MessageFormatError::UnsupportedCompression(9))
+        ));
+        assert!(matches!(
+            u64::from_compressed_binary(&[]),
+            Err(MessageFormatError::BinaryDeserializeError)
+        ));
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn deflate() {
+        let msg = vec![7u64; 1000];
+        let bytes = msg.to_compressed_binary(Compression::Deflate).unwrap();
+        assert_eq!(bytes[0], 1);
+        assert!(bytes.len() < msg.binary_size_hint().unwrap() / 10);
+        assert_eq!(Vec::<u64>::from_compressed_binary(&bytes).unwrap(), msg);
+        assert!(Vec::<u64>::from_compressed_binary(&bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn deflate_bomb() {
+        let bytes = vec![0u8; 10_000].to_compressed_binary(Compression::Deflate).unwrap();
+        assert!(bytes.len() < 100);
+        let size = 8 + 10_000;
+        assert_eq!(inflate(&bytes[1..], size).unwrap().len(), size);
+        assert!(matches!(
+            inflate(&bytes[1..], size - 1),
+            Err(MessageFormatError::DecompressedTooLarge(limit)) if limit == size - 1
+        ));
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A self-describing container for persisted messages. An envelope records the payload's encoding and a schema version
+//! chosen by the application, so that files written by older releases can be recognised and migrated, and ends with a
+//! CRC-32 so that corrupted files are rejected before any attempt to decode them.
+//!
+//! The layout is: the magic bytes `TENV`, a format tag byte, a big-endian `u16` version, a big-endian `u32` payload
+//! length, the payload, and a big-endian CRC-32 of everything before it.
+
+use super::{MessageFormat, MessageFormatError};
+use crate::checksum::crc32;
+use std::{convert::TryFrom, marker::PhantomData};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum EnvelopeError {
+    #[error("The data is not an envelope")]
+    InvalidMagic,
+    #[error("The envelope is truncated")]
+    Truncated,
+    #[error("The envelope has {0} bytes of trailing data")]
+    TrailingData(usize),
+    #[error("Unknown payload format {0}")]
+    UnknownFormat(u8),
+    #[error("The envelope checksum does not match. The data is corrupt")]
+    ChecksumMismatch,
+    #[error("The payload is too large for an envelope")]
+    PayloadTooLarge,
+}
+
+const MAGIC: &[u8; 4] = b"TENV";
+const HEADER_SIZE: usize = 4 + 1 + 2 + 4;
+const CHECKSUM_SIZE: usize = 4;
+
+/// How the payload of an [Envelope] is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadFormat {
+    /// [MessageFormat::to_binary]
+    Binary,
+    /// [MessageFormat::to_json]
+    Json,
+}
+
+impl PayloadFormat {
+    fn tag(self) -> u8 {
+        match self {
+            PayloadFormat::Binary => 0,
+            PayloadFormat::Json => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self, EnvelopeError> {
+        match tag {
+            0 => Ok(PayloadFormat::Binary),
+            1 => Ok(PayloadFormat::Json),
+            _ => Err(EnvelopeError::UnknownFormat(tag)),
+        }
+    }
+}
+
+/// An opened envelope, holding a payload that was written as a `T` at some version
+///
+/// ```
+/// use tari_utilities::message_format::envelope::{Envelope, PayloadFormat};
+///
+/// let sealed = Envelope::seal(&vec![1u32, 2, 3], 2, PayloadFormat::Binary).unwrap();
+/// let envelope = Envelope::<Vec<u32>>::open(&sealed).unwrap();
+/// assert_eq!(envelope.version(), 2);
+/// assert_eq!(envelope.message().unwrap(), vec![1, 2, 3]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Envelope<T> {
+    format: PayloadFormat,
+    version: u16,
+    payload: Vec<u8>,
+    _message: PhantomData<T>,
+}
+
+impl<T: MessageFormat> Envelope<T> {
+    /// Encode `message` in the given format and wrap it in an envelope marked with `version`
+    pub fn seal(message: &T, version: u16, format: PayloadFormat) -> Result<Vec<u8>, MessageFormatError> {
+        let payload = match format {
+            PayloadFormat::Binary => message.to_binary()?,
+            PayloadFormat::Json => message.to_json()?.into_bytes(),
+        };
+        let length = u32::try_from(payload.len()).map_err(|_| EnvelopeError::PayloadTooLarge)?;
+        let mut sealed = Vec::with_capacity(HEADER_SIZE + payload.len() + CHECKSUM_SIZE);
+        sealed.extend_from_slice(MAGIC);
+        sealed.push(format.tag());
+        sealed.extend_from_slice(&version.to_be_bytes());
+        sealed.extend_from_slice(&length.to_be_bytes());
+        sealed.extend_from_slice(&payload);
+        let checksum = crc32(&sealed);
+        sealed.extend_from_slice(&checksum.to_be_bytes());
+        Ok(sealed)
+    }
+
+    /// Check the framing and checksum of an envelope written by [Envelope::seal]. The payload is not decoded until
+    /// [Envelope::message] is called, so the version can be checked first.
+    pub fn open(bytes: &[u8]) -> Result<Self, EnvelopeError> {
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(EnvelopeError::InvalidMagic);
+        }
+        if bytes.len() < HEADER_SIZE + CHECKSUM_SIZE {
+            return Err(EnvelopeError::Truncated);
+        }
+        let length = u32::from_be_bytes([bytes[7], bytes[8], bytes[9], bytes[10]]) as usize;
+        // A hostile length can overflow on 32-bit targets, and can never fit in the input anyway
+        let end = HEADER_SIZE.checked_add(length).ok_or(EnvelopeError::Truncated)?;
+        let total = end.checked_add(CHECKSUM_SIZE).ok_or(EnvelopeError::Truncated)?;
+        if bytes.len() < total {
+            return Err(EnvelopeError::Truncated);
+        }
+        if bytes.len() > total {
+            return Err(EnvelopeError::TrailingData(bytes.len() - total));
+        }
+        if bytes[end..] != crc32(&bytes[..end]).to_be_bytes()[..] {
+            return Err(EnvelopeError::ChecksumMismatch);
+        }
+        Ok(Envelope {
+            format: PayloadFormat::from_tag(bytes[4])?,
+            version: u16::from_be_bytes([bytes[5], bytes[6]]),
+            payload: bytes[HEADER_SIZE..end].to_vec(),
+            _message: PhantomData,
+        })
+    }
+
+    /// The version the message was sealed with
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// The encoding of the payload
+    pub fn format(&self) -> PayloadFormat {
+        self.format
+    }
+
+    /// The encoded payload
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Decode the payload as a `T`
+    pub fn message(&self) -> Result<T, MessageFormatError> {
+        self.decode()
+    }
+
+    /// Decode the payload as some other type, e.g. the previous version of `T` when migrating old files
+    pub fn decode<U: MessageFormat>(&self) -> Result<U, MessageFormatError> {
+        match self.format {
+            PayloadFormat::Binary => U::from_binary(&self.payload),
+            PayloadFormat::Json => {
+                let json =
+                    std::str::from_utf8(&self.payload).map_err(|_| MessageFormatError::BinaryDeserializeError)?;
+                U::from_json(json)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct WalletV1 {
+        balance: u64,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct WalletV2 {
+        balance: u64,
+        label: String,
+    }
+
+    #[test]
+    fn seal_and_open() {
+        let wallet = WalletV2 {
+            balance: 10,
+            label: "savings".to_string(),
+        };
+        for &format in &[PayloadFormat::Binary, PayloadFormat::Json] {
+            let sealed = Envelope::seal(&wallet, 2, format).unwrap();
+            assert_eq!(&sealed[..4], b"TENV");
+            let envelope = Envelope::<WalletV2>::open(&sealed).unwrap();
+            assert_eq!(envelope.format(), format);
+            assert_eq!(envelope.version(), 2);
+            assert_eq!(envelope.message().unwrap(), wallet);
+        }
+        let json = Envelope::seal(&wallet, 2, PayloadFormat::Json).unwrap();
+        assert_eq!(
+            Envelope::<WalletV2>::open(&json).unwrap().payload(),
+            &br#"{"balance":10,"label":"savings"}"#[..]
+        );
+    }
+
+    #[test]
+    fn migration() {
+        let sealed = Envelope::seal(&WalletV1 { balance: 5 }, 1, PayloadFormat::Binary).unwrap();
+        let envelope = Envelope::<WalletV2>::open(&sealed).unwrap();
+        let wallet = match envelope.version() {
+            1 => {
+                let old = envelope.decode::<WalletV1>().unwrap();
+                WalletV2 {
+                    balance: old.balance,
+                    label: String::new(),
+                }
+            },
+            _ => envelope.message().unwrap(),
+        };
+        assert_eq!(wallet.balance, 5);
+    }
+
+    #[test]
+    fn corruption() {
+        let sealed = Envelope::seal(&WalletV1 { balance: 5 }, 1, PayloadFormat::Binary).unwrap();
+        let open = |bytes: &[u8]| Envelope::<WalletV1>::open(bytes).err();
+        assert_eq!(open(b"TEN"), Some(EnvelopeError::InvalidMagic));
+        assert_eq!(open(b"XENV\0\0\0\0\0\0\0\0\0\0\0"), Some(EnvelopeError::InvalidMagic));
+        assert_eq!(open(&sealed[..sealed.len() - 1]), Some(EnvelopeError::Truncated));
+        let mut huge = sealed.clone();
+        huge[7..11].copy_from_slice(&u32::max_value().to_be_bytes());
+        assert_eq!(open(&huge), Some(EnvelopeError::Truncated));
+        assert_eq!(
+            open(&[&sealed[..], &[0]].concat()),
+            Some(EnvelopeError::TrailingData(1))
+        );
+        let mut flipped = sealed.clone();
+        flipped[HEADER_SIZE] ^= 1;
+        assert_eq!(open(&flipped), Some(EnvelopeError::ChecksumMismatch));
+        let mut unknown = sealed[..sealed.len() - CHECKSUM_SIZE].to_vec();
+        unknown[4] = 9;
+        let checksum = crc32(&unknown);
+        unknown.extend_from_slice(&checksum.to_be_bytes());
+        assert_eq!(open(&unknown), Some(EnvelopeError::UnknownFormat(9)));
+    }
+}
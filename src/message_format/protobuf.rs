@@ -0,0 +1,179 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! [MessageFormat] for protobuf messages generated by prost. A blanket implementation would overlap with the one for
+//! serde types, so messages are wrapped in [Protobuf] instead. The binary form is the protobuf encoding, so it can be
+//! exchanged with gRPC peers; JSON goes through the message's serde implementation, which prost can be configured to
+//! derive with `type_attribute`.
+
+use super::{canonical, Base64Variant, MessageFormat, MessageFormatError};
+use serde::{de::DeserializeOwned, Serialize};
+use std::ops::{Deref, DerefMut};
+
+/// Wraps a prost message so that it implements [MessageFormat] with protobuf as its binary encoding
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use tari_utilities::message_format::{MessageFormat, Protobuf};
+///
+/// #[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+/// struct Ping {
+///     #[prost(uint64, tag = "1")]
+///     nonce: u64,
+/// }
+///
+/// let ping = Protobuf(Ping { nonce: 150 });
+/// assert_eq!(ping.to_binary().unwrap(), vec![0x08, 0x96, 0x01]);
+/// assert_eq!(ping.to_json().unwrap(), r#"{"nonce":150}"#);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Protobuf<M>(pub M);
+
+impl<M> Protobuf<M> {
+    pub fn into_inner(self) -> M {
+        self.0
+    }
+}
+
+impl<M> From<M> for Protobuf<M> {
+    fn from(message: M) -> Self {
+        Protobuf(message)
+    }
+}
+
+impl<M> Deref for Protobuf<M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.0
+    }
+}
+
+impl<M> DerefMut for Protobuf<M> {
+    fn deref_mut(&mut self) -> &mut M {
+        &mut self.0
+    }
+}
+
+impl<M> MessageFormat for Protobuf<M>
+where M: prost::Message + Default + Serialize + DeserializeOwned
+{
+    fn to_binary(&self) -> Result<Vec<u8>, MessageFormatError> {
+        let mut buf = Vec::with_capacity(self.0.encoded_len());
+        self.0
+            .encode(&mut buf)
+            .map_err(|_| MessageFormatError::BinarySerializeError)?;
+        Ok(buf)
+    }
+
+    fn to_json(&self) -> Result<String, MessageFormatError> {
+        self.0.to_json()
+    }
+
+    fn to_base64(&self) -> Result<String, MessageFormatError> {
+        self.to_base64_with(Base64Variant::Standard)
+    }
+
+    fn to_json_canonical(&self) -> Result<String, MessageFormatError> {
+        canonical::to_json_canonical(&self.0)
+    }
+
+    fn from_binary(msg: &[u8]) -> Result<Self, MessageFormatError> {
+        M::decode(msg)
+            .map(Protobuf)
+            .map_err(|_| MessageFormatError::BinaryDeserializeError)
+    }
+
+    fn from_json(msg: &str) -> Result<Self, MessageFormatError> {
+        M::from_json(msg).map(Protobuf)
+    }
+
+    fn from_base64(msg: &str) -> Result<Self, MessageFormatError> {
+        Self::from_base64_with(msg, Base64Variant::Standard)
+    }
+
+    fn binary_size_hint(&self) -> Option<usize> {
+        Some(self.0.encoded_len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::message_format::Compression;
+    use serde::Deserialize;
+
+    #[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+    struct Peer {
+        #[prost(bytes, tag = "1")]
+        public_key: Vec<u8>,
+        #[prost(string, repeated, tag = "2")]
+        addresses: Vec<String>,
+        #[prost(uint64, tag = "3")]
+        last_seen: u64,
+    }
+
+    fn peer() -> Protobuf<Peer> {
+        Protobuf(Peer {
+            public_key: vec![1, 2, 3],
+            addresses: vec!["/ip4/127.0.0.1/tcp/18189".to_string()],
+            last_seen: 1_600_000_000,
+        })
+    }
+
+    #[test]
+    fn binary() {
+        let peer = peer();
+        let bin = peer.to_binary().unwrap();
+        assert_eq!(&bin[..5], &[0x0a, 0x03, 1, 2, 3]);
+        assert_eq!(peer.binary_size_hint(), Some(bin.len()));
+        assert_eq!(Protobuf::<Peer>::from_binary(&bin).unwrap(), peer);
+        assert!(Protobuf::<Peer>::from_binary(&bin[..bin.len() - 1]).is_err());
+
+        let mut buf = Vec::new();
+        peer.to_binary_writer(&mut buf).unwrap();
+        assert_eq!(buf, bin);
+        assert_eq!(Protobuf::<Peer>::from_binary_reader(&buf[..]).unwrap(), peer);
+
+        let b64 = peer.to_base64().unwrap();
+        assert_eq!(b64, base64::encode(&bin));
+        assert_eq!(Protobuf::<Peer>::from_base64(&b64).unwrap(), peer);
+
+        let compressed = peer.to_compressed_binary(Compression::None).unwrap();
+        assert_eq!(Protobuf::<Peer>::from_compressed_binary(&compressed).unwrap(), peer);
+    }
+
+    #[test]
+    fn json() {
+        let peer = peer();
+        let json = peer.to_json().unwrap();
+        assert_eq!(
+            json,
+            r#"{"public_key":[1,2,3],"addresses":["/ip4/127.0.0.1/tcp/18189"],"last_seen":1600000000}"#
+        );
+        assert_eq!(Protobuf::<Peer>::from_json(&json).unwrap(), peer);
+        assert_eq!(
+            peer.to_json_canonical().unwrap(),
+            r#"{"addresses":["/ip4/127.0.0.1/tcp/18189"],"last_seen":1600000000,"public_key":[1,2,3]}"#
+        );
+    }
+}
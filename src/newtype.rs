@@ -0,0 +1,360 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+/// Declares a newtype around a fixed-size byte array, e.g. for keys, hashes and nonces.
+///
+/// The generated type implements [ByteArray](crate::ByteArray) (and hence [Hex](crate::hex::Hex)),
+/// [FixedByteArray](crate::FixedByteArray), [ByteArrayRef](crate::ByteArrayRef), `Display` as hex, `Debug`, `Default`,
+/// `Hash`, `PartialEq`/`Eq` and serde `Serialize`/`Deserialize`. Serialization uses a hex string for human-readable
+/// formats and raw bytes otherwise.
+///
+/// Appending `constant_time_eq` makes the `PartialEq` implementation compare every byte regardless of where the first
+/// difference is, which should be used for secret material. `Debug` then prints a mask instead of the contents, so the
+/// secret doesn't end up in logs.
+///
+/// With the `borsh` feature, the type also implements `BorshSerialize` and `BorshDeserialize`, as the raw bytes.
+///
+/// ```
+/// use tari_utilities::{hex::Hex, newtype, ByteArray};
+///
+/// newtype!(
+///     /// A block hash
+///     pub BlockHash, 32
+/// );
+/// newtype!(pub Mac, 16, constant_time_eq);
+///
+/// let hash = BlockHash::from_bytes(&[1u8; 32]).unwrap();
+/// assert_eq!(hash.to_hex(), "01".repeat(32));
+/// assert!(Mac::from_bytes(&[0u8; 15]).is_err());
+/// ```
+#[macro_export]
+macro_rules! newtype {
+    ($(#[$meta:meta])* $vis:vis $name:ident, $len:expr) => {
+        $crate::newtype!(@define $(#[$meta])* $vis $name, $len);
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.0[..] == other.0[..]
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "{}({})", stringify!($name), $crate::hex::to_hex(&self.0))
+            }
+        }
+    };
+
+    ($(#[$meta:meta])* $vis:vis $name:ident, $len:expr, constant_time_eq) => {
+        $crate::newtype!(@define $(#[$meta])* $vis $name, $len);
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                $crate::newtype::__private::constant_time_eq(&self.0, &other.0)
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "{}(****)", stringify!($name))
+            }
+        }
+    };
+
+    (@define $(#[$meta:meta])* $vis:vis $name:ident, $len:expr) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy)]
+        #[repr(transparent)]
+        $vis struct $name([u8; $len]);
+
+        #[allow(dead_code)]
+        impl $name {
+            /// Wrap the given byte array
+            pub fn from_array(bytes: [u8; $len]) -> Self {
+                $name(bytes)
+            }
+
+            /// Return a reference to the underlying byte array
+            pub fn as_array(&self) -> &[u8; $len] {
+                &self.0
+            }
+        }
+
+        $crate::impl_byte_array!($name, $len);
+
+        impl $crate::ByteArrayRef for $name {
+            fn from_bytes_ref(bytes: &[u8]) -> Result<&Self, $crate::ByteArrayError> {
+                if bytes.len() != $len {
+                    return Err($crate::ByteArrayError::IncorrectLength {
+                        expected: $len,
+                        actual: bytes.len(),
+                    });
+                }
+                // SAFETY: the type is a `repr(transparent)` wrapper around `[u8; $len]`, which has an alignment of 1,
+                // and the length was checked above
+                Ok(unsafe { &*(bytes.as_ptr() as *const Self) })
+            }
+        }
+
+        // Arrays longer than 32 bytes don't implement the standard traits, so these are implemented by hand
+        impl Default for $name {
+            fn default() -> Self {
+                $name([0u8; $len])
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl ::std::hash::Hash for $name {
+            fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                self.0[..].hash(state)
+            }
+        }
+
+        $crate::__newtype_borsh!($name, $len);
+    };
+}
+
+/// Implements borsh for a `newtype!`. The `cfg` has to be checked here, in this crate, since an attribute inside
+/// `newtype!` would test the features of the crate invoking it.
+#[cfg(feature = "borsh")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __newtype_borsh {
+    ($name:ident, $len:expr) => {
+        impl $crate::newtype::__private::borsh::BorshSerialize for $name {
+            fn serialize<W: ::std::io::Write>(&self, writer: &mut W) -> ::std::io::Result<()> {
+                writer.write_all(&self.0)
+            }
+        }
+
+        impl $crate::newtype::__private::borsh::BorshDeserialize for $name {
+            fn deserialize(buf: &mut &[u8]) -> ::std::io::Result<Self> {
+                if buf.len() < $len {
+                    return Err(::std::io::Error::new(
+                        ::std::io::ErrorKind::InvalidInput,
+                        "Unexpected length of input",
+                    ));
+                }
+                let mut bytes = [0u8; $len];
+                bytes.copy_from_slice(&buf[..$len]);
+                *buf = &buf[$len..];
+                Ok($name(bytes))
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "borsh"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __newtype_borsh {
+    ($name:ident, $len:expr) => {};
+}
+
+/// Implements [ByteArray](crate::ByteArray) (and hence [Hex](crate::hex::Hex)),
+/// [FixedByteArray](crate::FixedByteArray), `AsRef<[u8]>`, `Display` as hex and serde `Serialize`/`Deserialize` for an
+/// existing tuple struct that wraps a fixed-size byte array, e.g. one declared by another macro. Serialization is the
+/// same as for [newtype!].
+///
+/// ```
+/// use tari_utilities::{hex::Hex, impl_byte_array, ByteArray};
+///
+/// #[derive(Debug, PartialEq)]
+/// pub struct Commitment([u8; 32]);
+/// impl_byte_array!(Commitment, 32);
+///
+/// let c = Commitment::from_bytes(&[2u8; 32]).unwrap();
+/// assert_eq!(c.to_string(), "02".repeat(32));
+/// ```
+#[macro_export]
+macro_rules! impl_byte_array {
+    ($name:ident, $len:expr) => {
+        impl $crate::ByteArray for $name {
+            fn from_bytes(bytes: &[u8]) -> Result<Self, $crate::ByteArrayError> {
+                if bytes.len() != $len {
+                    return Err($crate::ByteArrayError::IncorrectLength {
+                        expected: $len,
+                        actual: bytes.len(),
+                    });
+                }
+                let mut buf = [0u8; $len];
+                buf.copy_from_slice(bytes);
+                Ok($name(buf))
+            }
+
+            fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl $crate::FixedByteArray for $name {
+            type Array = [u8; $len];
+
+            const LEN: usize = $len;
+
+            fn from_array(array: [u8; $len]) -> Self {
+                $name(array)
+            }
+
+            fn as_array(&self) -> &[u8; $len] {
+                &self.0
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(&$crate::hex::to_hex(&self.0))
+            }
+        }
+
+        impl $crate::newtype::__private::serde::Serialize for $name {
+            fn serialize<S>(&self, ser: S) -> Result<S::Ok, S::Error>
+            where S: $crate::newtype::__private::serde::Serializer {
+                $crate::serde::hex::serialize(self, ser)
+            }
+        }
+
+        impl<'de> $crate::newtype::__private::serde::Deserialize<'de> for $name {
+            fn deserialize<D>(de: D) -> Result<Self, D::Error>
+            where D: $crate::newtype::__private::serde::Deserializer<'de> {
+                $crate::serde::hex::deserialize(de)
+            }
+        }
+    };
+}
+
+/// Support code for the `newtype!` and `impl_byte_array!` macros. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "borsh")]
+    pub use borsh;
+    pub use serde;
+
+    /// Compare two equal-length byte slices without short-circuiting on the first difference
+    #[inline(never)]
+    pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+        let diff = a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+        diff == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "message_format")]
+    use crate::message_format::MessageFormat;
+    use crate::{hex::Hex, ByteArray, ByteArrayRef, FixedByteArray};
+    use std::collections::HashSet;
+
+    newtype!(Nonce, 24);
+    newtype!(pub(crate) Signature, 64, constant_time_eq);
+
+    #[derive(Debug, PartialEq)]
+    struct Commitment(pub [u8; 32]);
+    impl_byte_array!(Commitment, 32);
+
+    #[test]
+    fn byte_array() {
+        let nonce = Nonce::from_bytes(&[7u8; 24]).unwrap();
+        assert_eq!(nonce.as_array(), &[7u8; 24]);
+        assert_eq!(nonce.to_hex(), "07".repeat(24));
+        assert_eq!(format!("{}", nonce), "07".repeat(24));
+        assert_eq!(format!("{:?}", nonce), format!("Nonce({})", "07".repeat(24)));
+        assert!(Nonce::from_bytes(&[7u8; 25]).is_err());
+        assert_eq!(Nonce::default(), Nonce::from_array([0u8; 24]));
+        let set = vec![nonce, nonce, Nonce::default()].into_iter().collect::<HashSet<_>>();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn long_arrays() {
+        let mut bytes = [0u8; 64];
+        bytes[63] = 1;
+        let sig = Signature::from_array(bytes);
+        assert_eq!(sig, Signature::from_bytes(&bytes).unwrap());
+        assert_ne!(sig, Signature::default());
+        assert_eq!(Signature::from_hex(&sig.to_hex()).unwrap(), sig);
+        assert_eq!(<Signature as FixedByteArray>::LEN, 64);
+        assert_eq!(FixedByteArray::as_array(&sig)[63], 1);
+        fn copy<T: FixedByteArray>(value: &T) -> T {
+            T::from_array(*value.as_array())
+        }
+        assert_eq!(copy(&sig), sig);
+        let mut wire = vec![0xffu8];
+        wire.extend_from_slice(&bytes);
+        assert_eq!(Signature::from_bytes_ref(&wire[1..]).unwrap(), &sig);
+        assert!(Signature::from_bytes_ref(&wire).is_err());
+        assert_eq!(format!("{:?}", sig), "Signature(****)");
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh() {
+        use borsh::{BorshDeserialize, BorshSerialize};
+        let nonce = Nonce::from_array([5u8; 24]);
+        let bytes = nonce.try_to_vec().unwrap();
+        assert_eq!(bytes, vec![5u8; 24]);
+        assert_eq!(Nonce::try_from_slice(&bytes).unwrap(), nonce);
+        assert!(Nonce::try_from_slice(&bytes[1..]).is_err());
+        assert!(Nonce::try_from_slice(&[&bytes[..], &[0]].concat()).is_err());
+        let sig = Signature::from_array([0x22; 64]);
+        assert_eq!(Signature::try_from_slice(&sig.try_to_vec().unwrap()).unwrap(), sig);
+    }
+
+    #[test]
+    fn existing_struct() {
+        let c = Commitment::from_hex(&"09".repeat(32)).unwrap();
+        assert_eq!(c, Commitment([9; 32]));
+        assert_eq!(format!("{}", c), "09".repeat(32));
+        assert_eq!(<Commitment as FixedByteArray>::LEN, 32);
+        assert!(Commitment::from_bytes(&[9; 33]).is_err());
+    }
+
+    #[cfg(feature = "message_format")]
+    #[test]
+    fn serde() {
+        let c = Commitment([9; 32]);
+        assert_eq!(Commitment::from_json(&c.to_json().unwrap()).unwrap(), c);
+
+        let nonce = Nonce::from_array([0xab; 24]);
+        let json = nonce.to_json().unwrap();
+        assert_eq!(json, format!("\"{}\"", "ab".repeat(24)));
+        assert_eq!(Nonce::from_json(&json).unwrap(), nonce);
+        assert!(Nonce::from_json("\"abab\"").is_err());
+
+        let bin = nonce.to_binary().unwrap();
+        // A u64 length prefix followed by the raw bytes
+        assert_eq!(bin.len(), 8 + 24);
+        assert_eq!(Nonce::from_binary(&bin).unwrap(), nonce);
+        let sig = Signature::from_array([0x11; 64]);
+        assert_eq!(Signature::from_binary(&sig.to_binary().unwrap()).unwrap(), sig);
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{convert::TryFrom, fmt, str::FromStr};
+use thiserror::Error;
+
+const PPM_PER_PERCENT: u64 = 10_000;
+const PPM_PER_UNIT: u64 = 100 * PPM_PER_PERCENT;
+/// The number of decimal places of a percentage that can be represented exactly in parts per million
+const DECIMAL_PLACES: usize = 4;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PercentageError {
+    #[error("A percentage must be a decimal number followed by '%', e.g. \"12.5%\"")]
+    InvalidFormat,
+    #[error("A percentage can have at most 4 decimal places")]
+    TooPrecise,
+    #[error("The percentage is too large to be represented")]
+    Overflow,
+}
+
+/// A ratio expressed as a percentage, stored as an integer number of parts per million so that it can be applied to
+/// amounts without floating point rounding surprises. Values above 100% are permitted.
+///
+/// Percentages are displayed and serialized as strings such as `"12.5%"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Percentage(u64);
+
+impl Percentage {
+    pub const ONE_HUNDRED: Percentage = Percentage(PPM_PER_UNIT);
+    pub const ZERO: Percentage = Percentage(0);
+
+    /// Create a percentage from a number of parts per million, e.g. 125_000 is 12.5%
+    pub fn from_ppm(ppm: u64) -> Percentage {
+        Percentage(ppm)
+    }
+
+    /// Create a percentage from a whole number of percent
+    pub fn from_percent(percent: u64) -> Result<Percentage, PercentageError> {
+        percent
+            .checked_mul(PPM_PER_PERCENT)
+            .map(Percentage)
+            .ok_or(PercentageError::Overflow)
+    }
+
+    /// Return the percentage as a number of parts per million
+    pub fn as_ppm(self) -> u64 {
+        self.0
+    }
+
+    /// Return this percentage of `amount`, rounded down. Returns `None` if the result does not fit in a u64, which
+    /// can only happen for percentages above 100%.
+    pub fn checked_mul(self, amount: u64) -> Option<u64> {
+        let product = u128::from(amount) * u128::from(self.0) / u128::from(PPM_PER_UNIT);
+        u64::try_from(product).ok()
+    }
+
+    /// Return 100% minus this percentage, e.g. the share left over after a fee split. Returns `None` if this
+    /// percentage is greater than 100%.
+    pub fn complement(self) -> Option<Percentage> {
+        PPM_PER_UNIT.checked_sub(self.0).map(Percentage)
+    }
+
+    /// Returns true if `count` out of `total` meets or exceeds this percentage
+    pub fn is_met_by(self, count: u64, total: u64) -> bool {
+        u128::from(count) * u128::from(PPM_PER_UNIT) >= u128::from(total) * u128::from(self.0)
+    }
+}
+
+impl fmt::Display for Percentage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = self.0 / PPM_PER_PERCENT;
+        let fraction = self.0 % PPM_PER_PERCENT;
+        if fraction == 0 {
+            return write!(f, "{}%", whole);
+        }
+        let digits = format!("{:04}", fraction);
+        write!(f, "{}.{}%", whole, digits.trim_end_matches('0'))
+    }
+}
+
+impl FromStr for Percentage {
+    type Err = PercentageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if !s.ends_with('%') {
+            return Err(PercentageError::InvalidFormat);
+        }
+        let number = &s[..s.len() - 1];
+        let mut parts = number.splitn(2, '.');
+        let whole = parts.next().unwrap_or("");
+        let fraction = parts.next();
+        if whole.is_empty() || !whole.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(PercentageError::InvalidFormat);
+        }
+        let mut ppm = whole
+            .parse::<u64>()
+            .map_err(|_| PercentageError::Overflow)?
+            .checked_mul(PPM_PER_PERCENT)
+            .ok_or(PercentageError::Overflow)?;
+        if let Some(fraction) = fraction {
+            if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(PercentageError::InvalidFormat);
+            }
+            let fraction = fraction.trim_end_matches('0');
+            if fraction.len() > DECIMAL_PLACES {
+                return Err(PercentageError::TooPrecise);
+            }
+            let padded = format!("{:0<width$}", fraction, width = DECIMAL_PLACES);
+            let fraction_ppm = padded.parse::<u64>().map_err(|_| PercentageError::InvalidFormat)?;
+            ppm = ppm.checked_add(fraction_ppm).ok_or(PercentageError::Overflow)?;
+        }
+        Ok(Percentage(ppm))
+    }
+}
+
+impl Serialize for Percentage {
+    fn serialize<S>(&self, ser: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        ser.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Percentage {
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let s = String::deserialize(de)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "message_format")]
+    use crate::message_format::MessageFormat;
+
+    #[test]
+    fn parse_and_display() {
+        let cases = [
+            ("0%", 0),
+            ("12.5%", 125_000),
+            ("100%", 1_000_000),
+            ("0.0001%", 1),
+            ("33.3333%", 333_333),
+            ("150%", 1_500_000),
+        ];
+        for (s, ppm) in cases.iter() {
+            let p = Percentage::from_str(s).unwrap();
+            assert_eq!(p.as_ppm(), *ppm);
+            assert_eq!(&p.to_string(), s);
+        }
+        assert_eq!(Percentage::from_str(" 12.50% ").unwrap().as_ppm(), 125_000);
+    }
+
+    #[test]
+    fn parse_errors() {
+        for s in &["12.5", "%", ".5%", "5.%", "-5%", "1e2%", "12,5%", "five%"] {
+            assert_eq!(Percentage::from_str(s), Err(PercentageError::InvalidFormat), "{}", s);
+        }
+        assert_eq!(Percentage::from_str("0.00001%"), Err(PercentageError::TooPrecise));
+        assert_eq!(
+            Percentage::from_str("99999999999999999999%"),
+            Err(PercentageError::Overflow)
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        let fee = Percentage::from_str("12.5%").unwrap();
+        assert_eq!(fee.checked_mul(1_000), Some(125));
+        assert_eq!(fee.checked_mul(7), Some(0));
+        assert_eq!(fee.complement(), Some(Percentage::from_ppm(875_000)));
+        assert_eq!(
+            Percentage::ONE_HUNDRED.checked_mul(u64::max_value()),
+            Some(u64::max_value())
+        );
+        assert_eq!(
+            Percentage::from_percent(200).unwrap().checked_mul(u64::max_value()),
+            None
+        );
+        assert_eq!(Percentage::from_percent(101).unwrap().complement(), None);
+        let two_thirds = Percentage::from_str("66.6666%").unwrap();
+        assert!(two_thirds.is_met_by(2, 3));
+        assert!(!two_thirds.is_met_by(1, 3));
+        assert!(Percentage::ZERO.is_met_by(0, 0));
+    }
+
+    #[cfg(feature = "message_format")]
+    #[test]
+    fn serde() {
+        let p = Percentage::from_ppm(125_000);
+        assert_eq!(p.to_json().unwrap(), "\"12.5%\"");
+        assert_eq!(Percentage::from_json("\"12.5%\"").unwrap(), p);
+        assert!(Percentage::from_json("12.5").is_err());
+    }
+}
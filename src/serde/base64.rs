@@ -0,0 +1,86 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Serialize a [ByteArray] as a standard, padded base64 string in human-readable formats, such as JSON, and as raw
+//! bytes otherwise. Use with `#[serde(with = "tari_utilities::serde::base64")]`. This is the base64 counterpart of
+//! [super::hex].
+
+use crate::ByteArray;
+use serde::{Deserializer, Serializer};
+
+/// Serialize `value` as base64 or raw bytes, depending on whether the format is human-readable
+pub fn serialize<T, S>(value: &T, ser: S) -> Result<S::Ok, S::Error>
+where
+    T: ByteArray,
+    S: Serializer,
+{
+    if ser.is_human_readable() {
+        ser.serialize_str(&::base64::encode(value.as_bytes()))
+    } else {
+        ser.serialize_bytes(value.as_bytes())
+    }
+}
+
+/// Deserialize a value written by [serialize]
+pub fn deserialize<'de, T, D>(de: D) -> Result<T, D::Error>
+where
+    T: ByteArray,
+    D: Deserializer<'de>,
+{
+    super::deserialize_byte_array(de, "a base64 string or a byte array", |s| {
+        ::base64::decode(s).map_err(|e| e.to_string())
+    })
+}
+
+#[cfg(all(test, feature = "message_format"))]
+mod test {
+    use crate::message_format::MessageFormat;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Signature {
+        #[serde(with = "crate::serde::base64")]
+        nonce: [u8; 32],
+        #[serde(with = "crate::serde::base64")]
+        data: Vec<u8>,
+    }
+
+    #[test]
+    fn human_readable_and_binary() {
+        let sig = Signature {
+            nonce: [0xff; 32],
+            data: b"foobar".to_vec(),
+        };
+        let json = sig.to_json().unwrap();
+        assert_eq!(
+            json,
+            format!(r#"{{"nonce":"{}","data":"Zm9vYmFy"}}"#, "/".repeat(42) + "8=")
+        );
+        assert_eq!(Signature::from_json(&json).unwrap(), sig);
+        assert!(Signature::from_json(r#"{"nonce":"AA==","data":"Zm9vYmFy"}"#).is_err());
+        assert!(Signature::from_json(&json.replace("Zm9vYmFy", "Zm9vYmF!")).is_err());
+
+        let bin = sig.to_binary().unwrap();
+        assert_eq!(bin.len(), 8 + 32 + 8 + 6);
+        assert_eq!(Signature::from_binary(&bin).unwrap(), sig);
+    }
+}
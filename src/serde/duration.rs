@@ -0,0 +1,230 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Serde helpers for [Duration] fields, such as timeouts in config files. Pick the representation with the submodule:
+//! `#[serde(with = "tari_utilities::serde::duration::seconds")]`, `...::millis` or `...::human`, which uses strings
+//! such as `1h30m` or `250ms`.
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+use std::{fmt, time::Duration};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DurationError {
+    #[error("'{0}' is not a duration such as 1h30m or 250ms")]
+    InvalidFormat(String),
+    #[error("'{0}' is not a duration unit. Use d, h, m, s, ms, us or ns")]
+    UnknownUnit(String),
+    #[error("The duration is too long")]
+    Overflow,
+}
+
+/// A whole number of seconds. Any fraction of a second is dropped when serializing.
+pub mod seconds {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_u64(duration.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+        u64::deserialize(de).map(Duration::from_secs)
+    }
+}
+
+/// A whole number of milliseconds. Any fraction of a millisecond is dropped when serializing.
+pub mod millis {
+    use super::*;
+    use serde::ser;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, ser: S) -> Result<S::Ok, S::Error> {
+        let millis = duration.as_millis();
+        if millis > u128::from(u64::max_value()) {
+            return Err(ser::Error::custom(DurationError::Overflow));
+        }
+        ser.serialize_u64(millis as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+        u64::deserialize(de).map(Duration::from_millis)
+    }
+}
+
+/// A string such as `1h30m`, `90s` or `1d 2h`, in any format
+pub mod human {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_str(&format_duration(*duration))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+        struct HumanVisitor;
+
+        impl<'de> de::Visitor<'de> for HumanVisitor {
+            type Value = Duration;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a duration such as 1h30m")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Duration, E> {
+                parse_duration(v).map_err(E::custom)
+            }
+        }
+
+        de.deserialize_str(HumanVisitor)
+    }
+}
+
+/// The units of [format_duration] and [parse_duration], largest first, with their length in nanoseconds
+const UNITS: [(&str, u128); 7] = [
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+/// Format a duration such as `1h30m`, using the largest units first and leaving out zero components
+pub fn format_duration(duration: Duration) -> String {
+    let mut nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0s".to_string();
+    }
+    let mut s = String::new();
+    for &(unit, length) in &UNITS {
+        if nanos >= length {
+            s.push_str(&format!("{}{}", nanos / length, unit));
+            nanos %= length;
+        }
+    }
+    s
+}
+
+/// Parse a duration written as a number followed by a unit (`d`, `h`, `m`, `s`, `ms`, `us` or `ns`), or a sequence of
+/// them such as `1h30m` or `1d 12h`
+pub fn parse_duration(s: &str) -> Result<Duration, DurationError> {
+    let invalid = || DurationError::InvalidFormat(s.to_string());
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    let mut nanos = 0u128;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let letters = rest[digits..]
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .map_or(rest.len(), |i| digits + i);
+        if digits == 0 || letters == digits {
+            return Err(invalid());
+        }
+        let number = rest[..digits].parse::<u128>().map_err(|_| DurationError::Overflow)?;
+        let unit = &rest[digits..letters];
+        let length = UNITS
+            .iter()
+            .find(|(u, _)| *u == unit)
+            .map(|&(_, length)| length)
+            .ok_or_else(|| DurationError::UnknownUnit(unit.to_string()))?;
+        nanos = number
+            .checked_mul(length)
+            .and_then(|n| n.checked_add(nanos))
+            .ok_or(DurationError::Overflow)?;
+        rest = rest[letters..].trim_start();
+    }
+    let seconds = nanos / 1_000_000_000;
+    if seconds > u128::from(u64::max_value()) {
+        return Err(DurationError::Overflow);
+    }
+    Ok(Duration::new(seconds as u64, (nanos % 1_000_000_000) as u32))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn human_strings() {
+        for (s, duration) in &[
+            ("0s", Duration::from_secs(0)),
+            ("1h30m", Duration::from_secs(5400)),
+            ("1d2h3m4s", Duration::from_secs(93_784)),
+            ("250ms", Duration::from_millis(250)),
+            ("1s500ms", Duration::from_millis(1500)),
+            ("1m1ns", Duration::new(60, 1)),
+        ] {
+            assert_eq!(format_duration(*duration), *s);
+            assert_eq!(parse_duration(s), Ok(*duration));
+        }
+        assert_eq!(parse_duration(" 1h 30m "), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("90m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2s2s"), Ok(Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn invalid_strings() {
+        for s in &["", "h", "1", "1h30", "-1s", "1.5h"] {
+            assert_eq!(
+                parse_duration(s),
+                Err(DurationError::InvalidFormat((*s).to_string())),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(parse_duration("3w"), Err(DurationError::UnknownUnit("w".to_string())));
+        assert_eq!(
+            parse_duration("99999999999999999999999999d"),
+            Err(DurationError::Overflow)
+        );
+    }
+
+    #[cfg(feature = "message_format")]
+    #[test]
+    fn fields() {
+        use crate::message_format::MessageFormat;
+        use serde::Serialize;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Timeouts {
+            #[serde(with = "crate::serde::duration::seconds")]
+            connect: Duration,
+            #[serde(with = "crate::serde::duration::millis")]
+            ping: Duration,
+            #[serde(with = "crate::serde::duration::human")]
+            session: Duration,
+        }
+
+        let timeouts = Timeouts {
+            connect: Duration::from_secs(30),
+            ping: Duration::from_millis(1500),
+            session: Duration::from_secs(5400),
+        };
+        let json = timeouts.to_json().unwrap();
+        assert_eq!(json, r#"{"connect":30,"ping":1500,"session":"1h30m"}"#);
+        assert_eq!(Timeouts::from_json(&json).unwrap(), timeouts);
+        assert!(Timeouts::from_json(r#"{"connect":30,"ping":1500,"session":"soon"}"#).is_err());
+        let bin = timeouts.to_binary().unwrap();
+        assert_eq!(Timeouts::from_binary(&bin).unwrap(), timeouts);
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Serialize a [ByteArray] as a hex string in human-readable formats, such as JSON, and as raw bytes otherwise. Use
+//! with `#[serde(with = "tari_utilities::serde::hex")]`. Deserialization also accepts a sequence of bytes, which is
+//! how some binary formats encode byte strings.
+
+use crate::{hex::from_hex, ByteArray};
+use serde::{Deserializer, Serializer};
+
+/// Serialize `value` as hex or raw bytes, depending on whether the format is human-readable
+pub fn serialize<T, S>(value: &T, ser: S) -> Result<S::Ok, S::Error>
+where
+    T: ByteArray,
+    S: Serializer,
+{
+    if ser.is_human_readable() {
+        ser.serialize_str(&crate::hex::to_hex(value.as_bytes()))
+    } else {
+        ser.serialize_bytes(value.as_bytes())
+    }
+}
+
+/// Deserialize a value written by [serialize]
+pub fn deserialize<'de, T, D>(de: D) -> Result<T, D::Error>
+where
+    T: ByteArray,
+    D: Deserializer<'de>,
+{
+    super::deserialize_byte_array(de, "a hex string or a byte array", |s| {
+        from_hex(s).map_err(|e| e.to_string())
+    })
+}
+
+/// Serializes a borrowed [ByteArray] with [serialize], for use inside containers
+struct HexRef<'a, T>(&'a T);
+
+impl<T: ByteArray> serde::Serialize for HexRef<'_, T> {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize(self.0, ser)
+    }
+}
+
+/// The same as [super], but for `Option<T>` fields: `None` is serialized as `null` in JSON. Add `#[serde(default)]`
+/// to the field as well to accept a missing value.
+pub mod opt {
+    use super::HexRef;
+    use crate::{serde::SerdeByteArray, ByteArray};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serialize `Some` as hex or raw bytes, depending on whether the format is human-readable, and `None` as none
+    pub fn serialize<T, S>(value: &Option<T>, ser: S) -> Result<S::Ok, S::Error>
+    where
+        T: ByteArray,
+        S: Serializer,
+    {
+        match value {
+            Some(v) => ser.serialize_some(&HexRef(v)),
+            None => ser.serialize_none(),
+        }
+    }
+
+    /// Deserialize a value written by [serialize]
+    pub fn deserialize<'de, T, D>(de: D) -> Result<Option<T>, D::Error>
+    where
+        T: ByteArray,
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<SerdeByteArray<T>>::deserialize(de)?.map(SerdeByteArray::into_inner))
+    }
+}
+
+/// The same as [super], but for `Vec<T>` fields, which become a sequence of hex strings in human-readable formats and a
+/// sequence of byte strings otherwise
+pub mod vec {
+    use super::HexRef;
+    use crate::{serde::SerdeByteArray, ByteArray};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serialize each value as hex or raw bytes, depending on whether the format is human-readable
+    pub fn serialize<T, S>(values: &[T], ser: S) -> Result<S::Ok, S::Error>
+    where
+        T: ByteArray,
+        S: Serializer,
+    {
+        ser.collect_seq(values.iter().map(HexRef))
+    }
+
+    /// Deserialize values written by [serialize]
+    pub fn deserialize<'de, T, D>(de: D) -> Result<Vec<T>, D::Error>
+    where
+        T: ByteArray,
+        D: Deserializer<'de>,
+    {
+        let values = Vec::<SerdeByteArray<T>>::deserialize(de)?;
+        Ok(values.into_iter().map(SerdeByteArray::into_inner).collect())
+    }
+}
+
+/// The same as [super], but for the values of a map such as `HashMap<String, T>` or `BTreeMap<u64, T>`. The keys are
+/// serialized as usual.
+pub mod map_values {
+    use super::HexRef;
+    use crate::{
+        serde::{cautious_capacity, SerdeByteArray},
+        ByteArray,
+    };
+    use serde::{
+        de::{MapAccess, Visitor},
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    };
+    use std::{fmt, iter::FromIterator, marker::PhantomData};
+
+    /// Serialize each value as hex or raw bytes, depending on whether the format is human-readable
+    pub fn serialize<'a, K, V, M, S>(map: &'a M, ser: S) -> Result<S::Ok, S::Error>
+    where
+        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Serialize + 'a,
+        V: ByteArray + 'a,
+        S: Serializer,
+    {
+        ser.collect_map(map.into_iter().map(|(k, v)| (k, HexRef(v))))
+    }
+
+    /// Deserialize a map written by [serialize]
+    pub fn deserialize<'de, K, V, M, D>(de: D) -> Result<M, D::Error>
+    where
+        K: Deserialize<'de>,
+        V: ByteArray,
+        M: FromIterator<(K, V)>,
+        D: Deserializer<'de>,
+    {
+        struct MapVisitor<K, V, M>(PhantomData<(K, V, M)>);
+
+        impl<'de, K, V, M> Visitor<'de> for MapVisitor<K, V, M>
+        where
+            K: Deserialize<'de>,
+            V: ByteArray,
+            M: FromIterator<(K, V)>,
+        {
+            type Value = M;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a map")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<M, A::Error> {
+                let mut entries = Vec::with_capacity(cautious_capacity(access.size_hint()));
+                while let Some((k, v)) = access.next_entry::<K, SerdeByteArray<V>>()? {
+                    entries.push((k, v.into_inner()));
+                }
+                Ok(entries.into_iter().collect())
+            }
+        }
+
+        de.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+/// The same as [super], but for the keys of a map such as `HashMap<T, V>` or `BTreeMap<T, V>`, which lets maps keyed
+/// by hashes or public keys be written as JSON objects, whose keys must be strings. The values are serialized as usual.
+pub mod map_keys {
+    use super::HexRef;
+    use crate::{
+        serde::{cautious_capacity, SerdeByteArray},
+        ByteArray,
+    };
+    use serde::{
+        de::{MapAccess, Visitor},
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    };
+    use std::{fmt, iter::FromIterator, marker::PhantomData};
+
+    /// Serialize each key as hex or raw bytes, depending on whether the format is human-readable
+    pub fn serialize<'a, K, V, M, S>(map: &'a M, ser: S) -> Result<S::Ok, S::Error>
+    where
+        &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: ByteArray + 'a,
+        V: Serialize + 'a,
+        S: Serializer,
+    {
+        ser.collect_map(map.into_iter().map(|(k, v)| (HexRef(k), v)))
+    }
+
+    /// Deserialize a map written by [serialize]
+    pub fn deserialize<'de, K, V, M, D>(de: D) -> Result<M, D::Error>
+    where
+        K: ByteArray,
+        V: Deserialize<'de>,
+        M: FromIterator<(K, V)>,
+        D: Deserializer<'de>,
+    {
+        struct MapVisitor<K, V, M>(PhantomData<(K, V, M)>);
+
+        impl<'de, K, V, M> Visitor<'de> for MapVisitor<K, V, M>
+        where
+            K: ByteArray,
+            V: Deserialize<'de>,
+            M: FromIterator<(K, V)>,
+        {
+            type Value = M;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a map")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<M, A::Error> {
+                let mut entries = Vec::with_capacity(cautious_capacity(access.size_hint()));
+                while let Some((k, v)) = access.next_entry::<SerdeByteArray<K>, V>()? {
+                    entries.push((k.into_inner(), v));
+                }
+                Ok(entries.into_iter().collect())
+            }
+        }
+
+        de.deserialize_map(MapVisitor(PhantomData))
+    }
+}
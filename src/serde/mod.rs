@@ -0,0 +1,361 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Helpers for serializing this crate's types, and types built on it, with serde. The submodules are intended for use
+//! with `#[serde(with = "...")]`.
+
+#[cfg(feature = "base64")]
+pub mod base64;
+pub mod duration;
+pub mod hex;
+pub mod number_from_string;
+#[cfg(feature = "epoch_time")]
+pub mod rfc3339;
+pub mod string;
+
+use crate::ByteArray;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+/// A transparent wrapper that serializes any [ByteArray] as a hex string in human-readable formats and as raw bytes
+/// otherwise (see [hex]), so that fields don't each need a `#[serde(with = "...")]` attribute.
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use tari_utilities::serde::SerdeByteArray;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Block {
+///     hash: SerdeByteArray<[u8; 32]>,
+/// }
+///
+/// let block = Block {
+///     hash: SerdeByteArray([1u8; 32]),
+/// };
+/// let json = serde_json::to_string(&block).unwrap();
+/// assert_eq!(json, format!("{{\"hash\":\"{}\"}}", "01".repeat(32)));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SerdeByteArray<T>(pub T);
+
+impl<T> SerdeByteArray<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for SerdeByteArray<T> {
+    fn from(value: T) -> Self {
+        SerdeByteArray(value)
+    }
+}
+
+impl<T> Deref for SerdeByteArray<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for SerdeByteArray<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: ByteArray> Serialize for SerdeByteArray<T> {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        hex::serialize(&self.0, ser)
+    }
+}
+
+impl<'de, T: ByteArray> Deserialize<'de> for SerdeByteArray<T> {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        hex::deserialize(de).map(SerdeByteArray)
+    }
+}
+
+/// Deserialize an optional [ByteArray] written by [hex::opt], treating an empty value the same as `None`. Pair it with
+/// [skip_serializing_empty_bytes] and `#[serde(default)]` so that empty blobs are left out of JSON and missing fields
+/// are read as `None`:
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Payment {
+///     #[serde(
+///         default,
+///         serialize_with = "tari_utilities::serde::hex::opt::serialize",
+///         deserialize_with = "tari_utilities::serde::none_if_empty",
+///         skip_serializing_if = "tari_utilities::serde::skip_serializing_empty_bytes"
+///     )]
+///     memo: Option<Vec<u8>>,
+/// }
+///
+/// let json = serde_json::to_string(&Payment { memo: Some(vec![]) }).unwrap();
+/// assert_eq!(json, "{}");
+/// let payment: Payment = serde_json::from_str(r#"{"memo":""}"#).unwrap();
+/// assert_eq!(payment, Payment { memo: None });
+/// ```
+///
+/// Skipped fields can't be read back from formats that aren't self-describing, such as the binary `MessageFormat`, so
+/// only use `skip_serializing_if` on types that are sent as JSON.
+pub fn none_if_empty<'de, T, D>(de: D) -> Result<Option<T>, D::Error>
+where
+    T: ByteArray,
+    D: Deserializer<'de>,
+{
+    Ok(hex::opt::deserialize::<T, D>(de)?.filter(|v| !v.as_bytes().is_empty()))
+}
+
+/// For `#[serde(skip_serializing_if = "...")]`: true if the value is `None` or empty
+pub fn skip_serializing_empty_bytes<T: ByteArray>(value: &Option<T>) -> bool {
+    value.as_ref().map_or(true, |v| v.as_bytes().is_empty())
+}
+
+/// Limit a collection's length hint before pre-allocating for it. The hint comes from the input, so a hostile length
+/// prefix could otherwise make deserialization abort with a capacity overflow or exhaust memory.
+fn cautious_capacity(hint: Option<usize>) -> usize {
+    hint.unwrap_or(0).min(4096)
+}
+
+/// Deserialize a [ByteArray] from a string, decoded with `decode`, in human-readable formats, and from raw bytes or a
+/// sequence of bytes otherwise
+fn deserialize_byte_array<'de, T, D>(
+    de: D,
+    expecting: &'static str,
+    decode: fn(&str) -> Result<Vec<u8>, String>,
+) -> Result<T, D::Error>
+where
+    T: ByteArray,
+    D: Deserializer<'de>,
+{
+    struct ByteArrayVisitor<T> {
+        expecting: &'static str,
+        decode: fn(&str) -> Result<Vec<u8>, String>,
+        marker: PhantomData<T>,
+    }
+
+    impl<'de, T: ByteArray> Visitor<'de> for ByteArrayVisitor<T> {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.expecting)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+            let bytes = (self.decode)(v).map_err(E::custom)?;
+            T::from_bytes(&bytes).map_err(E::custom)
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+            T::from_bytes(v).map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+            let mut bytes = Vec::with_capacity(cautious_capacity(seq.size_hint()));
+            while let Some(b) = seq.next_element::<u8>()? {
+                bytes.push(b);
+            }
+            T::from_bytes(&bytes).map_err(de::Error::custom)
+        }
+    }
+
+    let visitor = ByteArrayVisitor {
+        expecting,
+        decode,
+        marker: PhantomData,
+    };
+    if de.is_human_readable() {
+        de.deserialize_str(visitor)
+    } else {
+        de.deserialize_bytes(visitor)
+    }
+}
+
+#[cfg(all(test, feature = "message_format"))]
+mod test {
+    use super::*;
+    use crate::message_format::MessageFormat;
+    use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Output {
+        commitment: SerdeByteArray<Vec<u8>>,
+        #[serde(with = "crate::serde::hex")]
+        script: Vec<u8>,
+    }
+
+    #[test]
+    fn human_readable_and_binary() {
+        let output = Output {
+            commitment: vec![0xc0, 0xff, 0xee].into(),
+            script: vec![1, 2],
+        };
+        let json = output.to_json().unwrap();
+        assert_eq!(json, r#"{"commitment":"c0ffee","script":"0102"}"#);
+        assert_eq!(Output::from_json(&json).unwrap(), output);
+        assert!(Output::from_json(r#"{"commitment":"c0ffe","script":"0102"}"#).is_err());
+
+        let bin = output.to_binary().unwrap();
+        // Each field is a u64 length prefix followed by the raw bytes
+        assert_eq!(bin.len(), 8 + 3 + 8 + 2);
+        assert_eq!(Output::from_binary(&bin).unwrap(), output);
+        assert_eq!(*output.commitment, vec![0xc0, 0xff, 0xee]);
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Note {
+        #[serde(
+            default,
+            serialize_with = "crate::serde::hex::opt::serialize",
+            deserialize_with = "crate::serde::none_if_empty",
+            skip_serializing_if = "crate::serde::skip_serializing_empty_bytes"
+        )]
+        memo: Option<Vec<u8>>,
+    }
+
+    #[test]
+    fn empty_bytes() {
+        let note = Note { memo: Some(vec![1, 2]) };
+        assert_eq!(note.to_json().unwrap(), r#"{"memo":"0102"}"#);
+        assert_eq!(Note::from_json(r#"{"memo":"0102"}"#).unwrap(), note);
+        assert_eq!(Note { memo: None }.to_json().unwrap(), "{}");
+        assert_eq!(Note { memo: Some(vec![]) }.to_json().unwrap(), "{}");
+        for json in &["{}", r#"{"memo":null}"#, r#"{"memo":""}"#] {
+            assert_eq!(Note::from_json(json).unwrap(), Note { memo: None });
+        }
+        assert!(Note::from_json(r#"{"memo":"0"}"#).is_err());
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Block {
+        #[serde(with = "crate::serde::hex::vec")]
+        kernels: Vec<[u8; 32]>,
+        #[serde(with = "crate::serde::hex::map_values")]
+        scripts: BTreeMap<u64, Vec<u8>>,
+    }
+
+    #[test]
+    fn collections() {
+        let mut scripts = BTreeMap::new();
+        scripts.insert(7, vec![0xaa, 0xbb]);
+        let block = Block {
+            kernels: vec![[1; 32], [2; 32]],
+            scripts,
+        };
+        let json = block.to_json().unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"kernels":["{}","{}"],"scripts":{{"7":"aabb"}}}}"#,
+                "01".repeat(32),
+                "02".repeat(32)
+            )
+        );
+        assert_eq!(Block::from_json(&json).unwrap(), block);
+        assert!(Block::from_json(r#"{"kernels":["01"],"scripts":{}}"#).is_err());
+
+        let bin = block.to_binary().unwrap();
+        // Length-prefixed sequences of length-prefixed byte strings, with the map key as a u64
+        assert_eq!(bin.len(), 8 + 2 * (8 + 32) + 8 + 8 + 8 + 2);
+        assert_eq!(Block::from_binary(&bin).unwrap(), block);
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Balances {
+        #[serde(with = "crate::serde::hex::map_keys")]
+        by_key: HashMap<[u8; 32], u64>,
+    }
+
+    #[test]
+    fn map_keys() {
+        let mut by_key = HashMap::new();
+        by_key.insert([0xee; 32], 100);
+        let balances = Balances { by_key };
+        let json = balances.to_json().unwrap();
+        assert_eq!(json, format!(r#"{{"by_key":{{"{}":100}}}}"#, "ee".repeat(32)));
+        assert_eq!(Balances::from_json(&json).unwrap(), balances);
+        assert!(Balances::from_json(r#"{"by_key":{"ee":100}}"#).is_err());
+
+        let bin = balances.to_binary().unwrap();
+        assert_eq!(bin.len(), 8 + 8 + 32 + 8);
+        assert_eq!(Balances::from_binary(&bin).unwrap(), balances);
+    }
+
+    #[test]
+    fn hostile_map_length() {
+        let huge = u64::max_value().to_le_bytes();
+        assert!(Balances::from_binary(&huge).is_err());
+        let block = [&0u64.to_le_bytes()[..], &huge].concat();
+        assert!(Block::from_binary(&block).is_err());
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Peer {
+        #[serde(with = "crate::serde::hex::opt")]
+        public_key: Option<[u8; 32]>,
+        #[serde(default, with = "crate::serde::hex::opt")]
+        signature: Option<Vec<u8>>,
+    }
+
+    #[test]
+    fn optional_values() {
+        let peer = Peer {
+            public_key: Some([1; 32]),
+            signature: None,
+        };
+        let json = peer.to_json().unwrap();
+        assert_eq!(
+            json,
+            format!(r#"{{"public_key":"{}","signature":null}}"#, "01".repeat(32))
+        );
+        assert_eq!(Peer::from_json(&json).unwrap(), peer);
+        let missing = format!(r#"{{"public_key":"{}"}}"#, "01".repeat(32));
+        assert_eq!(Peer::from_json(&missing).unwrap(), peer);
+        assert!(Peer::from_json(r#"{"public_key":"0101"}"#).is_err());
+
+        let peer = Peer {
+            public_key: None,
+            signature: Some(vec![0xab]),
+        };
+        let bin = peer.to_binary().unwrap();
+        assert_eq!(Peer::from_binary(&bin).unwrap(), peer);
+        assert_eq!(
+            Peer::from_json(r#"{"public_key":null,"signature":"ab"}"#).unwrap(),
+            peer
+        );
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Serialize unsigned integers so that JavaScript clients read them correctly. JSON numbers above 2^53 - 1 lose
+//! precision when parsed as JavaScript numbers, so in human-readable formats larger values are written as strings,
+//! and both numbers and numeric strings are accepted when reading. Binary formats use the plain integer. Use with
+//! `#[serde(with = "tari_utilities::serde::number_from_string")]` on `u64` or `u128` fields.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{convert::TryFrom, fmt, marker::PhantomData};
+
+/// The largest integer that a JavaScript number holds exactly
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Serialize `value` as a number, or as a decimal string in human-readable formats when it exceeds
+/// [MAX_SAFE_INTEGER]
+pub fn serialize<T, S>(value: &T, ser: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + Copy + Into<u128> + fmt::Display,
+    S: Serializer,
+{
+    if ser.is_human_readable() && (*value).into() > u128::from(MAX_SAFE_INTEGER) {
+        ser.collect_str(value)
+    } else {
+        value.serialize(ser)
+    }
+}
+
+/// Deserialize an integer from either a number or a decimal string
+pub fn deserialize<'de, T, D>(de: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de> + TryFrom<u128>,
+    D: Deserializer<'de>,
+{
+    struct NumberVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: TryFrom<u128>> de::Visitor<'de> for NumberVisitor<T> {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("an unsigned integer or a string containing one")
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+            self.visit_u128(u128::from(v))
+        }
+
+        fn visit_u128<E: de::Error>(self, v: u128) -> Result<T, E> {
+            T::try_from(v).map_err(|_| E::custom(format!("{} is out of range", v)))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> {
+            let v = u64::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))?;
+            self.visit_u64(v)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+            let v = v
+                .parse::<u128>()
+                .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))?;
+            self.visit_u128(v)
+        }
+    }
+
+    if de.is_human_readable() {
+        de.deserialize_any(NumberVisitor(PhantomData))
+    } else {
+        T::deserialize(de)
+    }
+}
+
+#[cfg(all(test, feature = "message_format"))]
+mod test {
+    use crate::message_format::MessageFormat;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Transfer {
+        #[serde(with = "crate::serde::number_from_string")]
+        amount: u64,
+        #[serde(with = "crate::serde::number_from_string")]
+        total: u128,
+    }
+
+    #[test]
+    fn numbers_and_strings() {
+        let small = Transfer {
+            amount: 5,
+            total: 9_007_199_254_740_991,
+        };
+        let json = small.to_json().unwrap();
+        assert_eq!(json, r#"{"amount":5,"total":9007199254740991}"#);
+        assert_eq!(Transfer::from_json(&json).unwrap(), small);
+
+        let big = Transfer {
+            amount: u64::max_value(),
+            total: 9_007_199_254_740_992,
+        };
+        let json = big.to_json().unwrap();
+        assert_eq!(json, r#"{"amount":"18446744073709551615","total":"9007199254740992"}"#);
+        assert_eq!(Transfer::from_json(&json).unwrap(), big);
+        assert_eq!(
+            Transfer::from_json(r#"{"amount":"5","total":9007199254740991}"#).unwrap(),
+            small
+        );
+
+        assert!(Transfer::from_json(r#"{"amount":"18446744073709551616","total":0}"#).is_err());
+        assert!(Transfer::from_json(r#"{"amount":-1,"total":0}"#).is_err());
+        assert!(Transfer::from_json(r#"{"amount":"1.5","total":0}"#).is_err());
+        assert!(Transfer::from_json(r#"{"amount":1.5,"total":0}"#).is_err());
+
+        let bin = big.to_binary().unwrap();
+        assert_eq!(bin.len(), 24);
+        assert_eq!(Transfer::from_binary(&bin).unwrap(), big);
+    }
+}
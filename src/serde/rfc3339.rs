@@ -0,0 +1,114 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Serialize an [EpochTime] as an RFC 3339 timestamp (e.g. `2020-01-01T00:00:00Z`) in human-readable formats, such as
+//! JSON and TOML, and as seconds since the UNIX epoch otherwise. Use with
+//! `#[serde(with = "tari_utilities::serde::rfc3339")]`. Deserialization accepts any UTC offset.
+
+use crate::epoch_time::EpochTime;
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use serde::{de, ser, Deserialize, Deserializer, Serializer};
+use std::fmt;
+
+/// Serialize `time` as an RFC 3339 string or as seconds, depending on whether the format is human-readable
+// `#[serde(with = "...")]` always passes the field by reference
+#[allow(clippy::trivially_copy_pass_by_ref)]
+pub fn serialize<S: Serializer>(time: &EpochTime, ser: S) -> Result<S::Ok, S::Error> {
+    if !ser.is_human_readable() {
+        return ser.serialize_u64(time.as_u64());
+    }
+    // Times too far in the future for chrono to represent can't be formatted
+    let naive = if time.as_u64() <= i64::max_value() as u64 {
+        NaiveDateTime::from_timestamp_opt(time.as_u64() as i64, 0)
+    } else {
+        None
+    };
+    let naive = naive.ok_or_else(|| ser::Error::custom(format!("{} is out of range for a date", time)))?;
+    let date = DateTime::<Utc>::from_utc(naive, Utc);
+    ser.serialize_str(&date.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// Deserialize a value written by [serialize]
+pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<EpochTime, D::Error> {
+    struct Rfc3339Visitor;
+
+    impl<'de> de::Visitor<'de> for Rfc3339Visitor {
+        type Value = EpochTime;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("an RFC 3339 timestamp")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<EpochTime, E> {
+            let date = DateTime::parse_from_rfc3339(v).map_err(E::custom)?;
+            let seconds = date.timestamp();
+            if seconds < 0 {
+                return Err(E::custom(format!("{} is before the UNIX epoch", v)));
+            }
+            Ok(EpochTime::from(seconds as u64))
+        }
+    }
+
+    if de.is_human_readable() {
+        de.deserialize_str(Rfc3339Visitor)
+    } else {
+        u64::deserialize(de).map(EpochTime::from)
+    }
+}
+
+#[cfg(all(test, feature = "message_format"))]
+mod test {
+    use super::*;
+    use crate::message_format::MessageFormat;
+    use serde::Serialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Header {
+        #[serde(with = "crate::serde::rfc3339")]
+        timestamp: EpochTime,
+    }
+
+    #[test]
+    fn human_readable_and_binary() {
+        let header = Header {
+            timestamp: EpochTime::from(1_577_836_800),
+        };
+        let json = header.to_json().unwrap();
+        assert_eq!(json, r#"{"timestamp":"2020-01-01T00:00:00Z"}"#);
+        assert_eq!(Header::from_json(&json).unwrap(), header);
+        assert_eq!(
+            Header::from_json(r#"{"timestamp":"2020-01-01T02:00:00+02:00"}"#).unwrap(),
+            header
+        );
+        assert!(Header::from_json(r#"{"timestamp":"1969-12-31T23:59:59Z"}"#).is_err());
+        assert!(Header::from_json(r#"{"timestamp":1577836800}"#).is_err());
+
+        let bin = header.to_binary().unwrap();
+        assert_eq!(bin, 1_577_836_800u64.to_le_bytes());
+        assert_eq!(Header::from_binary(&bin).unwrap(), header);
+
+        let far_future = Header {
+            timestamp: EpochTime::from(u64::max_value()),
+        };
+        assert!(far_future.to_json().is_err());
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Serialize any type as a string, using its [Display](std::fmt::Display) implementation, and deserialize it with
+//! [FromStr]. Use with `#[serde(with = "tari_utilities::serde::string")]`, e.g. for amounts and timestamps in config
+//! files that people edit by hand, or for `u64` values in JSON read by JavaScript, which can't represent them exactly.
+
+use serde::{de, Deserializer, Serializer};
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+/// Serialize `value` as the string produced by its `Display` implementation
+pub fn serialize<T, S>(value: &T, ser: S) -> Result<S::Ok, S::Error>
+where
+    T: fmt::Display,
+    S: Serializer,
+{
+    ser.collect_str(value)
+}
+
+/// Deserialize a string and parse it with `FromStr`
+pub fn deserialize<'de, T, D>(de: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+    D: Deserializer<'de>,
+{
+    struct FromStrVisitor<T>(PhantomData<T>);
+
+    impl<'de, T> de::Visitor<'de> for FromStrVisitor<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a string")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+            v.parse().map_err(E::custom)
+        }
+    }
+
+    de.deserialize_str(FromStrVisitor(PhantomData))
+}
+
+#[cfg(all(test, feature = "message_format"))]
+mod test {
+    use crate::message_format::MessageFormat;
+    use serde::{Deserialize, Serialize};
+    use std::net::Ipv4Addr;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        #[serde(with = "crate::serde::string")]
+        fee: u64,
+        #[serde(with = "crate::serde::string")]
+        address: Ipv4Addr,
+    }
+
+    #[test]
+    fn human_readable_and_binary() {
+        let config = Config {
+            fee: u64::max_value(),
+            address: Ipv4Addr::new(127, 0, 0, 1),
+        };
+        let json = config.to_json().unwrap();
+        assert_eq!(json, r#"{"fee":"18446744073709551615","address":"127.0.0.1"}"#);
+        assert_eq!(Config::from_json(&json).unwrap(), config);
+        assert!(Config::from_json(r#"{"fee":"-1","address":"127.0.0.1"}"#).is_err());
+        assert!(Config::from_json(r#"{"fee":1,"address":"127.0.0.1"}"#).is_err());
+
+        let bin = config.to_binary().unwrap();
+        assert_eq!(Config::from_binary(&bin).unwrap(), config);
+    }
+}
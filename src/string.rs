@@ -0,0 +1,432 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! String newtypes that enforce basic invariants at construction, so that empty or oversized values are rejected at
+//! the boundary rather than deep inside a consumer.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{convert::TryFrom, fmt, marker::PhantomData, ops::Deref, str::FromStr};
+use thiserror::Error;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum StringError {
+    #[error("The string may not be empty")]
+    Empty,
+    #[error("The string is {actual} bytes long, but at most {max} bytes are permitted")]
+    TooLong { max: usize, actual: usize },
+}
+
+/// A string that is guaranteed to contain at least one character
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NonEmptyString(String);
+
+impl NonEmptyString {
+    /// Wrap the given string, returning [StringError::Empty] if it is empty
+    pub fn new(value: String) -> Result<Self, StringError> {
+        if value.is_empty() {
+            return Err(StringError::Empty);
+        }
+        Ok(NonEmptyString(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+/// Marker types implementing this trait fix the maximum length, in bytes, of a [BoundedString]
+///
+/// ```
+/// use tari_utilities::string::{BoundedString, StringLimit};
+///
+/// pub struct WalletNameLimit;
+/// impl StringLimit for WalletNameLimit {
+///     const MAX: usize = 32;
+/// }
+///
+/// assert!(BoundedString::<WalletNameLimit>::new("savings".to_string()).is_ok());
+/// assert!(BoundedString::<WalletNameLimit>::new("x".repeat(33)).is_err());
+/// ```
+pub trait StringLimit {
+    const MAX: usize;
+}
+
+/// A string that is at most `L::MAX` bytes long when UTF-8 encoded
+pub struct BoundedString<L> {
+    value: String,
+    _limit: PhantomData<L>,
+}
+
+impl<L: StringLimit> BoundedString<L> {
+    /// Wrap the given string, returning [StringError::TooLong] if it exceeds the limit
+    pub fn new(value: String) -> Result<Self, StringError> {
+        if value.len() > L::MAX {
+            return Err(StringError::TooLong {
+                max: L::MAX,
+                actual: value.len(),
+            });
+        }
+        Ok(BoundedString {
+            value,
+            _limit: PhantomData,
+        })
+    }
+
+    /// The maximum length, in bytes, of this string type
+    pub fn max_len() -> usize {
+        L::MAX
+    }
+}
+
+impl<L> BoundedString<L> {
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    pub fn into_string(self) -> String {
+        self.value
+    }
+}
+
+// The impls below are written by hand since derives would place unnecessary bounds on the marker type
+
+impl<L> Clone for BoundedString<L> {
+    fn clone(&self) -> Self {
+        BoundedString {
+            value: self.value.clone(),
+            _limit: PhantomData,
+        }
+    }
+}
+
+impl<L> PartialEq for BoundedString<L> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<L> Eq for BoundedString<L> {}
+
+impl<L> fmt::Debug for BoundedString<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.value, f)
+    }
+}
+
+macro_rules! impl_string_traits {
+    ($name:ident $(<$l:ident: $bound:ident>)?) => {
+        impl$(<$l: $bound>)? TryFrom<String> for $name$(<$l>)? {
+            type Error = StringError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+
+        impl$(<$l: $bound>)? TryFrom<&str> for $name$(<$l>)? {
+            type Error = StringError;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                Self::new(value.to_string())
+            }
+        }
+
+        impl$(<$l: $bound>)? FromStr for $name$(<$l>)? {
+            type Err = StringError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::new(s.to_string())
+            }
+        }
+
+        impl$(<$l>)? Deref for $name$(<$l>)? {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                self.as_str()
+            }
+        }
+
+        impl$(<$l>)? AsRef<str> for $name$(<$l>)? {
+            fn as_ref(&self) -> &str {
+                self.as_str()
+            }
+        }
+
+        impl$(<$l>)? fmt::Display for $name$(<$l>)? {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl$(<$l>)? Serialize for $name$(<$l>)? {
+            fn serialize<S>(&self, ser: S) -> Result<S::Ok, S::Error>
+            where S: Serializer {
+                ser.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de $(, $l: $bound)?> Deserialize<'de> for $name$(<$l>)? {
+            fn deserialize<D>(de: D) -> Result<Self, D::Error>
+            where D: Deserializer<'de> {
+                let value = String::deserialize(de)?;
+                Self::new(value).map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+impl_string_traits!(NonEmptyString);
+impl_string_traits!(BoundedString<L: StringLimit>);
+
+/// A UTF-8 string for secrets such as passphrases. Its contents are zeroed when it is dropped, when it is truncated and
+/// when it grows into a new allocation, so no copies are left behind in freed memory. `Debug` and `Display` print a
+/// mask instead of the contents.
+#[cfg(feature = "zeroize")]
+#[derive(Default)]
+pub struct SafeString(String);
+
+#[cfg(feature = "zeroize")]
+impl SafeString {
+    pub fn new() -> Self {
+        SafeString(String::new())
+    }
+
+    /// Create an empty string with room for `capacity` bytes. Reserving enough up front avoids reallocating at all.
+    pub fn with_capacity(capacity: usize) -> Self {
+        SafeString(String::with_capacity(capacity))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Append `s`. If the current buffer is too small, the contents are copied to a larger one and the old buffer is
+    /// zeroed before it is freed.
+    pub fn push_str(&mut self, s: &str) {
+        let needed = self.0.len() + s.len();
+        if needed > self.0.capacity() {
+            let mut grown = String::with_capacity(needed.max(self.0.capacity() * 2));
+            grown.push_str(&self.0);
+            wipe(&mut std::mem::replace(&mut self.0, grown));
+        }
+        self.0.push_str(s);
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Shorten the string to `new_len` bytes, zeroing the removed bytes. Panics if `new_len` is not on a character
+    /// boundary, like [String::truncate].
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.0.len() {
+            assert!(self.0.is_char_boundary(new_len), "new_len is not on a char boundary");
+            // SAFETY: zero bytes are valid UTF-8, and `new_len` is on a character boundary
+            unsafe { self.0.as_mut_vec()[new_len..].zeroize() };
+            self.0.truncate(new_len);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SafeString {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+/// Zero the whole buffer of `s`, including any spare capacity beyond its length, and leave it empty
+#[cfg(feature = "zeroize")]
+fn wipe(s: &mut String) {
+    // SAFETY: the buffer is only extended with zero bytes, which are valid UTF-8, and is left empty
+    let bytes = unsafe { s.as_mut_vec() };
+    bytes.resize(bytes.capacity(), 0);
+    bytes.zeroize();
+}
+
+#[cfg(feature = "zeroize")]
+impl Clone for SafeString {
+    fn clone(&self) -> Self {
+        let mut clone = SafeString::with_capacity(self.0.capacity());
+        clone.push_str(&self.0);
+        clone
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl From<String> for SafeString {
+    /// Copy `value` into a buffer of its own and zero the original. Its spare capacity may still hold earlier contents,
+    /// such as a passphrase that was typed and then edited, so it can't be adopted as is.
+    fn from(mut value: String) -> Self {
+        let s = SafeString::from(value.as_str());
+        wipe(&mut value);
+        s
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl From<&str> for SafeString {
+    fn from(value: &str) -> Self {
+        let mut s = SafeString::with_capacity(value.len());
+        s.push_str(value);
+        s
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl PartialEq for SafeString {
+    /// Compare in constant time, so the time taken doesn't reveal how much of a guess was right
+    fn eq(&self, other: &Self) -> bool {
+        crate::newtype::__private::constant_time_eq(self.0.as_bytes(), other.0.as_bytes())
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Eq for SafeString {}
+
+#[cfg(feature = "zeroize")]
+impl fmt::Debug for SafeString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SafeString(****)")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl fmt::Display for SafeString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("****")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "message_format")]
+    use crate::message_format::MessageFormat;
+
+    struct Four;
+    impl StringLimit for Four {
+        const MAX: usize = 4;
+    }
+
+    #[test]
+    fn non_empty() {
+        assert_eq!(NonEmptyString::new(String::new()), Err(StringError::Empty));
+        let s = NonEmptyString::try_from("tari").unwrap();
+        assert_eq!(s.as_str(), "tari");
+        assert_eq!(s.len(), 4);
+        assert_eq!(s.to_string(), "tari");
+    }
+
+    #[test]
+    fn bounded() {
+        assert_eq!(BoundedString::<Four>::max_len(), 4);
+        assert!(BoundedString::<Four>::from_str("").is_ok());
+        let s = BoundedString::<Four>::from_str("abcd").unwrap();
+        assert_eq!(&*s, "abcd");
+        let err = BoundedString::<Four>::from_str("abcde").unwrap_err();
+        assert_eq!(err, StringError::TooLong { max: 4, actual: 5 });
+        assert_eq!(
+            err.to_string(),
+            "The string is 5 bytes long, but at most 4 bytes are permitted"
+        );
+        // The limit is in bytes, not characters
+        assert!(BoundedString::<Four>::from_str("ééé").is_err());
+    }
+
+    #[cfg(feature = "message_format")]
+    #[test]
+    fn serde() {
+        let s = NonEmptyString::try_from("tari").unwrap();
+        assert_eq!(s.to_json().unwrap(), "\"tari\"");
+        assert_eq!(NonEmptyString::from_json("\"tari\"").unwrap(), s);
+        assert!(NonEmptyString::from_json("\"\"").is_err());
+
+        let s = BoundedString::<Four>::from_str("abcd").unwrap();
+        assert_eq!(BoundedString::<Four>::from_json("\"abcd\"").unwrap(), s);
+        assert!(BoundedString::<Four>::from_json("\"abcde\"").is_err());
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn safe_string() {
+        let mut s = SafeString::with_capacity(4);
+        s.push_str("pass");
+        assert_eq!(s.capacity(), 4);
+        s.push('é');
+        assert!(s.capacity() >= 8);
+        s.push_str("phrase");
+        assert_eq!(s.as_str(), "passéphrase");
+        assert_eq!(s.len(), 12);
+        s.truncate(6);
+        assert_eq!(s.as_str(), "passé");
+        assert_eq!(s, SafeString::from("passé"));
+        assert_eq!(s.clone(), s);
+        assert_eq!(format!("{} {:?}", s, s), "**** SafeString(****)");
+        s.clear();
+        assert!(s.is_empty());
+        assert_eq!(SafeString::from("secret".to_string()).as_str(), "secret");
+        assert_ne!(s, SafeString::from("secreT"));
+        assert_ne!(SafeString::from("secret"), SafeString::from("secret!"));
+
+        let mut edited = String::with_capacity(16);
+        edited.push_str("hunter2");
+        edited.truncate(1);
+        let adopted = SafeString::from(edited);
+        assert_eq!(adopted.as_str(), "h");
+        // The spare capacity that held "unter2" is not carried over
+        assert_eq!(adopted.capacity(), 1);
+        let mut wiped = "hunter2".to_string();
+        wipe(&mut wiped);
+        assert!(wiped.is_empty());
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    #[should_panic]
+    fn safe_string_truncate_mid_char() {
+        SafeString::from("é").truncate(1);
+    }
+}
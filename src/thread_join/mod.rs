@@ -21,6 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub mod error;
+#[allow(clippy::module_inception)]
 pub mod thread_join;
 
 pub use self::{error::ThreadError, thread_join::ThreadJoinWithTimeout};
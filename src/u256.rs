@@ -0,0 +1,289 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{ByteArray, ByteArrayError, ByteArrayRef, FixedByteArray};
+use std::{fmt, str};
+
+/// A 256-bit unsigned integer, with just enough arithmetic for difficulty and target calculations.
+///
+/// The value is held in big-endian byte order so that it can be borrowed as a [ByteArray] (and hence converted to and
+/// from hex). Arithmetic is performed on four little-endian ordered `u64` limbs, see [U256::to_limbs].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct U256([u8; 32]);
+
+impl U256 {
+    /// The largest value that can be represented, 2^256 - 1
+    pub const MAX: U256 = U256([0xffu8; 32]);
+    /// The value zero
+    pub const ZERO: U256 = U256([0u8; 32]);
+
+    /// Create a value from its big-endian byte representation
+    pub fn from_be_bytes(bytes: [u8; 32]) -> U256 {
+        U256(bytes)
+    }
+
+    /// Create a value from its little-endian byte representation
+    pub fn from_le_bytes(mut bytes: [u8; 32]) -> U256 {
+        bytes.reverse();
+        U256(bytes)
+    }
+
+    /// Return the big-endian byte representation of the value
+    pub fn to_be_bytes(self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Return the little-endian byte representation of the value
+    pub fn to_le_bytes(self) -> [u8; 32] {
+        let mut bytes = self.0;
+        bytes.reverse();
+        bytes
+    }
+
+    /// Create a value from four `u64` limbs, least significant limb first
+    pub fn from_limbs(limbs: [u64; 4]) -> U256 {
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.rchunks_exact_mut(8).zip(limbs.iter()) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        U256(bytes)
+    }
+
+    /// Return the value as four `u64` limbs, least significant limb first
+    pub fn to_limbs(self) -> [u64; 4] {
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(self.0.rchunks_exact(8)) {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(chunk);
+            *limb = u64::from_be_bytes(buf);
+        }
+        limbs
+    }
+
+    /// Returns true if the value is zero
+    pub fn is_zero(&self) -> bool {
+        *self == U256::ZERO
+    }
+
+    /// Add two values, returning `None` on overflow
+    pub fn checked_add(self, rhs: U256) -> Option<U256> {
+        let mut limbs = self.to_limbs();
+        let mut carry = false;
+        for (limb, other) in limbs.iter_mut().zip(rhs.to_limbs().iter()) {
+            let (sum, c1) = limb.overflowing_add(*other);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        if carry {
+            None
+        } else {
+            Some(U256::from_limbs(limbs))
+        }
+    }
+
+    /// Subtract `rhs` from this value, returning `None` if the result would be negative
+    pub fn checked_sub(self, rhs: U256) -> Option<U256> {
+        let mut limbs = self.to_limbs();
+        let mut borrow = false;
+        for (limb, other) in limbs.iter_mut().zip(rhs.to_limbs().iter()) {
+            let (diff, b1) = limb.overflowing_sub(*other);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        if borrow {
+            None
+        } else {
+            Some(U256::from_limbs(limbs))
+        }
+    }
+
+    /// Multiply by a `u64`, returning `None` on overflow
+    pub fn checked_mul_u64(self, rhs: u64) -> Option<U256> {
+        let mut limbs = self.to_limbs();
+        let mut carry = 0u128;
+        for limb in limbs.iter_mut() {
+            let product = u128::from(*limb) * u128::from(rhs) + carry;
+            *limb = product as u64;
+            carry = product >> 64;
+        }
+        if carry == 0 {
+            Some(U256::from_limbs(limbs))
+        } else {
+            None
+        }
+    }
+
+    /// Divide by a `u64`, returning `None` if `rhs` is zero. This is typically used to derive a target from a
+    /// difficulty, i.e. `U256::MAX.checked_div_u64(difficulty)`.
+    pub fn checked_div_u64(self, rhs: u64) -> Option<U256> {
+        if rhs == 0 {
+            return None;
+        }
+        Some(self.div_rem_u64(rhs).0)
+    }
+
+    /// Long division by a non-zero `u64`, returning the quotient and remainder
+    fn div_rem_u64(self, rhs: u64) -> (U256, u64) {
+        let mut limbs = self.to_limbs();
+        let mut rem = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let current = (rem << 64) | u128::from(*limb);
+            *limb = (current / u128::from(rhs)) as u64;
+            rem = current % u128::from(rhs);
+        }
+        (U256::from_limbs(limbs), rem as u64)
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(value: u64) -> Self {
+        U256::from_limbs([value, 0, 0, 0])
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(value: u128) -> Self {
+        U256::from_limbs([value as u64, (value >> 64) as u64, 0, 0])
+    }
+}
+
+impl AsRef<[u8]> for U256 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// The byte representation of a U256 is always 32 bytes in big-endian order
+impl ByteArray for U256 {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        Ok(U256(<[u8; 32]>::from_bytes(bytes)?))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl ByteArrayRef for U256 {
+    fn from_bytes_ref(bytes: &[u8]) -> Result<&Self, ByteArrayError> {
+        let array = <[u8; 32]>::from_bytes_ref(bytes)?;
+        // SAFETY: U256 is a `repr(transparent)` wrapper around `[u8; 32]`
+        Ok(unsafe { &*(array as *const [u8; 32] as *const U256) })
+    }
+}
+
+impl FixedByteArray for U256 {
+    type Array = [u8; 32];
+
+    const LEN: usize = 32;
+
+    fn from_array(array: [u8; 32]) -> Self {
+        U256(array)
+    }
+
+    fn as_array(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// Displays the value in decimal
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.write_str("0");
+        }
+        let mut digits = Vec::with_capacity(78);
+        let mut value = *self;
+        while !value.is_zero() {
+            let (quotient, rem) = value.div_rem_u64(10);
+            digits.push(b'0' + rem as u8);
+            value = quotient;
+        }
+        digits.reverse();
+        f.write_str(str::from_utf8(&digits).map_err(|_| fmt::Error)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hex::Hex;
+
+    #[test]
+    fn byte_order() {
+        let mut be = [0u8; 32];
+        be[31] = 1;
+        be[0] = 0x80;
+        let v = U256::from_be_bytes(be);
+        assert_eq!(v.to_limbs(), [1, 0, 0, 0x8000_0000_0000_0000]);
+        let le = v.to_le_bytes();
+        assert_eq!(le[0], 1);
+        assert_eq!(le[31], 0x80);
+        assert_eq!(U256::from_le_bytes(le), v);
+        assert_eq!(U256::from_limbs(v.to_limbs()), v);
+        assert_eq!(U256::from_bytes_ref(&be).unwrap(), &v);
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(U256::from(1u64) < U256::from(2u64));
+        assert!(U256::from(u64::max_value()) < U256::from(1u128 << 64));
+        assert!(U256::MAX > U256::from(u128::max_value()));
+        assert!(U256::ZERO.is_zero());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = U256::from(u64::max_value());
+        assert_eq!(a.checked_add(U256::from(1u64)), Some(U256::from(1u128 << 64)));
+        assert_eq!(U256::MAX.checked_add(U256::from(1u64)), None);
+        assert_eq!(U256::from(1u128 << 64).checked_sub(U256::from(1u64)), Some(a));
+        assert_eq!(U256::ZERO.checked_sub(U256::from(1u64)), None);
+        assert_eq!(a.checked_mul_u64(2), Some(U256::from(u128::from(u64::max_value()) * 2)));
+        assert_eq!(U256::MAX.checked_mul_u64(2), None);
+        assert_eq!(U256::from(100u64).checked_div_u64(7), Some(U256::from(14u64)));
+        assert_eq!(U256::MAX.checked_div_u64(0), None);
+        let target = U256::MAX.checked_div_u64(1_000).unwrap();
+        assert!(target.checked_mul_u64(1_000).unwrap() <= U256::MAX);
+        assert!(target.checked_mul_u64(1_001).is_none());
+    }
+
+    #[test]
+    fn display_and_hex() {
+        assert_eq!(U256::ZERO.to_string(), "0");
+        assert_eq!(U256::from(1_234_567_890u64).to_string(), "1234567890");
+        assert_eq!(
+            U256::MAX.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        let v = U256::from(0xdead_beefu64);
+        assert_eq!(
+            v.to_hex(),
+            "00000000000000000000000000000000000000000000000000000000deadbeef"
+        );
+        assert_eq!(U256::from_hex(&v.to_hex()).unwrap(), v);
+        assert!(U256::from_bytes(&[1, 2, 3]).is_err());
+    }
+}
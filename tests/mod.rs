@@ -20,8 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+#![cfg(feature = "bit")]
+
 use std::mem;
-use tari_utilities::bit::checked_bits_to_uint;
+use tari_utilities::bit::{checked_bits_to_uint, deinterleave, deinterleave_bytes, interleave, interleave_bytes};
 
 #[test]
 fn shift_left_overflow_bits_to_uint() {
@@ -65,3 +67,30 @@ fn shift_left_overflow_bits_to_uint() {
     let result = checked_bits_to_uint(&bits);
     assert_eq!(result, None);
 }
+
+#[test]
+fn morton_codes() {
+    assert_eq!(interleave(0, 0), 0);
+    assert_eq!(interleave(1, 0), 1);
+    assert_eq!(interleave(0, 1), 2);
+    assert_eq!(interleave(0b101, 0b011), 0b01_10_11);
+    assert_eq!(interleave(u32::max_value(), 0), 0x5555_5555_5555_5555);
+    assert_eq!(interleave(u32::max_value(), u32::max_value()), u64::max_value());
+    for &(x, y) in &[(0, 0), (1, 2), (0xdead_beef, 0x1234_5678), (u32::max_value(), 7)] {
+        assert_eq!(deinterleave(interleave(x, y)), (x, y));
+    }
+}
+
+#[test]
+fn morton_code_bytes() {
+    let (x, y) = (0xdead_beefu32, 0x1234_5678u32);
+    let code = interleave_bytes(&x.to_be_bytes(), &y.to_be_bytes()).unwrap();
+    assert_eq!(code, interleave(x, y).to_be_bytes().to_vec());
+    assert_eq!(
+        deinterleave_bytes(&code),
+        Some((x.to_be_bytes().to_vec(), y.to_be_bytes().to_vec()))
+    );
+    assert_eq!(interleave_bytes(&[1, 2], &[3]), None);
+    assert_eq!(deinterleave_bytes(&[1, 2, 3]), None);
+    assert_eq!(interleave_bytes(&[], &[]), Some(vec![]));
+}